@@ -1,5 +1,7 @@
 use crate::constraints::ConstraintEvent;
+use crate::keybinds::{ActionState, InputAction};
 use crate::placing::PlacedPart;
+use crate::properties::PartProperties;
 use bevy::prelude::*;
 
 pub struct ActionsPlugin;
@@ -20,18 +22,21 @@ pub struct ActionList(pub Vec<Action>);
 pub enum Action {
     Placed(String, Entity),
     Constrained(ConstraintEvent),
-    Moved(Entity, Transform, Transform),
+    /// Every entity that moved together, with its transform before and after the move
+    Moved(Vec<(Entity, Transform, Transform)>),
     Deleted(Entity, String, Transform),
+    /// Entity of the `PatternFeature`
+    Patterned(Entity),
+    /// Mirrored copies of the selected parts
+    Mirrored(Vec<Entity>),
+    /// Entity of the new `Assembly`
+    Grouped(Entity),
+    /// Entity and its properties from before the edit
+    PropertiesChanged(Entity, PartProperties),
     PlaceHolder,
 }
 
 impl Action {
-    pub fn is_placed(&self) -> bool {
-        match self {
-            Self::Placed(_, _) => true,
-            _ => false,
-        }
-    }
     pub fn is_placeholder(&self) -> bool {
         match self {
             Self::PlaceHolder => true,
@@ -76,11 +81,13 @@ impl Into<ConstraintEvent> for Action {
 fn undo_move(
     mut commands: Commands,
     mut action_list: ResMut<ActionList>,
-    mut transform_query: Query<&mut Transform, With<crate::placing::Part>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut refresh_parts_list_writer: EventWriter<crate::ui::editor::parts_list::RefreshPartsList>,
+    mut transform_query: Query<&mut Transform>,
+    actions: Res<ActionState>,
+    pattern_query: Query<&crate::pattern::PatternFeature>,
+    mut mates: ResMut<crate::constraints::Mates>,
+    mut ungroup_writer: EventWriter<crate::assembly::UngroupEvent>,
 ) {
-    if keyboard.pressed(KeyCode::ControlLeft) && keyboard.just_pressed(KeyCode::KeyZ) {
+    if actions.just_pressed(InputAction::Undo) {
         if action_list.0.is_empty() {
             return;
         }
@@ -108,22 +115,39 @@ fn undo_move(
                     - constraint_event.constraints[0].transform.translation;
                 (*transform).translation += displacement;
                 (*transform).rotation = constraint_event.constraints[0].transform.rotation;
+                mates.remove(&constraint_event);
             }
             Action::Deleted(_bruh, _bruh1, _bruh2) => {
                 println!("Undoing a delete isn't supported yet!");
             }
+            Action::Patterned(feature_entity) => {
+                if let Ok(feature) = pattern_query.get(feature_entity) {
+                    crate::pattern::despawn_pattern(&mut commands, feature_entity, feature);
+                }
+            }
+            Action::Mirrored(copies) => {
+                for entity in copies {
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            Action::Grouped(assembly) => {
+                ungroup_writer.send(crate::assembly::UngroupEvent(assembly));
+            }
             Action::PlaceHolder => {
                 println!("PlaceHolder");
                 return;
             }
-            Action::Moved(_entity, _previous_transform, _current_transform) => {
-                //     commands.entity(entity).log_components();
-                //     let mut transform = transform_query.get_mut(entity).unwrap();
-                //     if transform.clone() != current_transform {
-                //         *transform = current_transform;
-                //     } else {
-                //         *transform = previous_transform;
-                //     }
+            Action::Moved(moves) => {
+                for (entity, previous_transform, _current_transform) in moves {
+                    if let Ok(mut transform) = transform_query.get_mut(entity) {
+                        *transform = previous_transform;
+                    }
+                }
+            }
+            Action::PropertiesChanged(entity, previous_properties) => {
+                if let Some(mut entity_commands) = commands.get_entity(entity) {
+                    previous_properties.apply(&mut entity_commands);
+                }
             }
         }
 
@@ -139,7 +163,5 @@ fn undo_move(
             action_list.0.remove(*index);
         }
         action_list.0.push(Action::PlaceHolder);
-
-        refresh_parts_list_writer.send(crate::ui::editor::parts_list::RefreshPartsList);
     }
 }
@@ -0,0 +1,397 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    actions::{Action, ActionList},
+    catalog::{find_by_scene_path, import_scale},
+    constraints::PendingConstraints,
+    keybinds::{ActionState, InputAction},
+    move_objects::{CurrentlyMoving, MoveObjectsState},
+    placing::{Part, PartName},
+    ui::editor::handle::{get_model_handle, ModelHandles},
+};
+
+mod ui;
+use ui::AssemblyUiPlugin;
+
+pub const ASSEMBLIES_FOLDER: &str = "assemblies";
+
+pub struct AssemblyPlugin;
+
+impl Plugin for AssemblyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<UngroupEvent>()
+            .add_plugins(AssemblyUiPlugin)
+            .add_systems(
+                Update,
+                (
+                    group_selection,
+                    ungroup_selection,
+                    ungroup_event.run_if(on_event::<UngroupEvent>()),
+                )
+                    .run_if(in_state(crate::ui::UIState::Editor)),
+            );
+    }
+}
+
+/// A named group of parts (and other assemblies) that moves as one unit
+#[derive(Component, Debug, Clone)]
+pub struct Assembly {
+    pub name: String,
+}
+
+/// Moves the assembly's children up a level and removes it
+#[derive(Event, Debug, Clone, Copy)]
+pub struct UngroupEvent(pub Entity);
+
+/// The outermost assembly that contains `entity`, or `entity` itself if it isn't in one
+pub fn top_level_assembly(
+    entity: Entity,
+    parent_query: &Query<&Parent>,
+    is_assembly: impl Fn(Entity) -> bool,
+) -> Entity {
+    let mut top_level = entity;
+    let mut current = entity;
+    while let Ok(parent) = parent_query.get(current) {
+        current = parent.get();
+        if is_assembly(current) {
+            top_level = current;
+        }
+    }
+    top_level
+}
+
+/// Groups the selection into a new assembly
+fn group_selection(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    selected_query: Query<(Entity, &GlobalTransform, Option<&Parent>), With<CurrentlyMoving>>,
+    global_transform_query: Query<&GlobalTransform>,
+    assembly_query: Query<(), With<Assembly>>,
+    mut action_list: ResMut<ActionList>,
+) {
+    if !actions.just_pressed(InputAction::Group) {
+        return;
+    }
+    if selected_query.is_empty() {
+        return;
+    }
+
+    // Nest the new assembly if everything selected lives in the same one
+    let parents: Vec<Option<Entity>> = selected_query
+        .iter()
+        .map(|(_, _, parent)| parent.map(|parent| parent.get()))
+        .collect();
+    let common_parent = parents[0].filter(|parent| {
+        assembly_query.contains(*parent) && parents.iter().all(|other| *other == Some(*parent))
+    });
+
+    let center = selected_query
+        .iter()
+        .map(|(_, global_transform, _)| global_transform.translation())
+        .sum::<Vec3>()
+        / selected_query.iter().count() as f32;
+    let assembly_global_transform = GlobalTransform::from_translation(center);
+    let assembly_transform = match common_parent {
+        Some(parent) => {
+            assembly_global_transform.reparented_to(global_transform_query.get(parent).unwrap())
+        }
+        None => assembly_global_transform.compute_transform(),
+    };
+
+    let assembly = commands
+        .spawn((
+            SpatialBundle::from_transform(assembly_transform),
+            Assembly {
+                name: format!("Assembly {}", assembly_query.iter().count() + 1),
+            },
+            CurrentlyMoving,
+        ))
+        .id();
+    if let Some(parent) = common_parent {
+        commands.entity(parent).add_child(assembly);
+    }
+
+    for (entity, global_transform, _) in selected_query.iter() {
+        commands
+            .entity(entity)
+            .remove::<CurrentlyMoving>()
+            .insert(global_transform.reparented_to(&assembly_global_transform))
+            .set_parent(assembly);
+    }
+
+    action_list.0.push(Action::Grouped(assembly));
+}
+
+/// Ungroups the selected assemblies
+fn ungroup_selection(
+    actions: Res<ActionState>,
+    selected_query: Query<Entity, (With<CurrentlyMoving>, With<Assembly>)>,
+    mut ungroup_events: EventWriter<UngroupEvent>,
+) {
+    if !actions.just_pressed(InputAction::Ungroup) {
+        return;
+    }
+    for entity in selected_query.iter() {
+        ungroup_events.send(UngroupEvent(entity));
+    }
+}
+
+fn ungroup_event(
+    mut commands: Commands,
+    mut events: EventReader<UngroupEvent>,
+    assembly_query: Query<(&Children, Option<&Parent>), With<Assembly>>,
+    member_query: Query<&GlobalTransform, Or<(With<PartName>, With<Assembly>)>>,
+    global_transform_query: Query<&GlobalTransform>,
+    mut moving_state: ResMut<NextState<MoveObjectsState>>,
+) {
+    for UngroupEvent(assembly) in events.read() {
+        let Ok((children, parent)) = assembly_query.get(*assembly) else {
+            continue;
+        };
+        for child in children.iter() {
+            let Ok(global_transform) = member_query.get(*child) else {
+                continue;
+            };
+            match parent {
+                Some(parent) => {
+                    let parent_global_transform = global_transform_query.get(parent.get()).unwrap();
+                    commands
+                        .entity(*child)
+                        .insert(global_transform.reparented_to(parent_global_transform))
+                        .set_parent(parent.get());
+                }
+                None => {
+                    commands
+                        .entity(*child)
+                        .insert(global_transform.compute_transform())
+                        .remove_parent();
+                }
+            }
+        }
+        commands.entity(*assembly).despawn_recursive();
+        moving_state.set(MoveObjectsState::NotMoving);
+    }
+}
+
+/// An assembly as it's stored in its own file
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct AssemblyFile {
+    pub name: String,
+    pub children: Vec<AssemblyNode>,
+}
+
+impl AssemblyFile {
+    /// Assembly files can be shared between computers, so the parts in them might not be in this
+    /// version's catalog
+    pub fn check_parts(&self) -> Result<(), String> {
+        let mut unknown: Vec<&str> = self
+            .children
+            .iter()
+            .flat_map(|node| node.part_names())
+            .filter(|part_name| find_by_scene_path(part_name).is_none())
+            .collect();
+        if unknown.is_empty() {
+            return Ok(());
+        }
+        unknown.sort();
+        unknown.dedup();
+        Err(format!("Unknown parts: {}", unknown.join(", ")))
+    }
+}
+
+/// A part or an assembly in an assembly file or the project file, with its transform relative to
+/// the assembly it's in. Parts are saved without their import scale, so files made before parts
+/// had one still load at the right size
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub enum AssemblyNode {
+    Part {
+        part_name: String,
+        transform: (Vec3, Vec4, Vec3),
+    },
+    Assembly {
+        name: String,
+        transform: (Vec3, Vec4, Vec3),
+        children: Vec<AssemblyNode>,
+    },
+}
+
+/// Takes the parts that aren't in the catalog out of `nodes` (and the assemblies in them), and
+/// returns their paths
+pub fn remove_unknown_parts(nodes: &mut Vec<AssemblyNode>) -> Vec<String> {
+    let mut unknown = vec![];
+    nodes.retain_mut(|node| match node {
+        AssemblyNode::Part { part_name, .. } => {
+            let known = find_by_scene_path(part_name).is_some();
+            if !known {
+                unknown.push(part_name.clone());
+            }
+            known
+        }
+        AssemblyNode::Assembly { children, .. } => {
+            unknown.extend(remove_unknown_parts(children));
+            true
+        }
+    });
+    unknown
+}
+
+impl AssemblyNode {
+    /// Paths of every part in the node, including the ones in nested assemblies
+    pub fn part_names(&self) -> Vec<&str> {
+        match self {
+            Self::Part { part_name, .. } => vec![part_name.as_str()],
+            Self::Assembly { children, .. } => children
+                .iter()
+                .flat_map(|child| child.part_names())
+                .collect(),
+        }
+    }
+}
+
+fn transform_to_tuple(transform: &Transform) -> (Vec3, Vec4, Vec3) {
+    (
+        transform.translation,
+        transform.rotation.to_array().into(),
+        transform.scale,
+    )
+}
+
+fn tuple_to_transform(transform: (Vec3, Vec4, Vec3)) -> Transform {
+    Transform {
+        translation: transform.0,
+        rotation: Quat::from_vec4(transform.1),
+        scale: transform.2,
+    }
+}
+
+/// Builds the file contents of everything under `assembly`, with transforms relative to it
+pub fn assembly_to_file(
+    assembly: Entity,
+    assembly_query: &Query<(&Assembly, &Transform, Option<&Children>)>,
+    part_query: &Query<(&PartName, &Transform)>,
+) -> Option<AssemblyFile> {
+    let (Assembly { name }, _, children) = assembly_query.get(assembly).ok()?;
+    Some(AssemblyFile {
+        name: name.clone(),
+        children: children_to_nodes(children, assembly_query, part_query),
+    })
+}
+
+/// Like [`assembly_to_file`], but keeps the assembly's own transform so it can go in the project
+/// file
+pub fn assembly_to_node(
+    assembly: Entity,
+    assembly_query: &Query<(&Assembly, &Transform, Option<&Children>)>,
+    part_query: &Query<(&PartName, &Transform)>,
+) -> Option<AssemblyNode> {
+    let (Assembly { name }, transform, children) = assembly_query.get(assembly).ok()?;
+    Some(AssemblyNode::Assembly {
+        name: name.clone(),
+        transform: transform_to_tuple(transform),
+        children: children_to_nodes(children, assembly_query, part_query),
+    })
+}
+
+fn children_to_nodes(
+    children: Option<&Children>,
+    assembly_query: &Query<(&Assembly, &Transform, Option<&Children>)>,
+    part_query: &Query<(&PartName, &Transform)>,
+) -> Vec<AssemblyNode> {
+    let mut nodes = vec![];
+    let Some(children) = children else {
+        return nodes;
+    };
+    for child in children.iter() {
+        if let Ok((part_name, transform)) = part_query.get(*child) {
+            let mut transform = *transform;
+            transform.scale /= import_scale(&part_name.0);
+            nodes.push(AssemblyNode::Part {
+                part_name: part_name.0.clone(),
+                transform: transform_to_tuple(&transform),
+            });
+        } else if let Ok((Assembly { name }, transform, children)) = assembly_query.get(*child) {
+            nodes.push(AssemblyNode::Assembly {
+                name: name.clone(),
+                transform: transform_to_tuple(transform),
+                children: children_to_nodes(children, assembly_query, part_query),
+            });
+        }
+    }
+    nodes
+}
+
+/// Spawns an assembly from its file, returns the entity of the outermost assembly
+pub fn spawn_assembly_file(
+    commands: &mut Commands,
+    file: &AssemblyFile,
+    transform: Transform,
+    model_handles: &ModelHandles,
+) -> Entity {
+    let assembly = commands
+        .spawn((
+            SpatialBundle::from_transform(transform),
+            Assembly {
+                name: file.name.clone(),
+            },
+        ))
+        .id();
+    spawn_nodes(commands, assembly, &file.children, model_handles);
+    assembly
+}
+
+fn spawn_nodes(
+    commands: &mut Commands,
+    parent: Entity,
+    nodes: &[AssemblyNode],
+    model_handles: &ModelHandles,
+) {
+    for node in nodes.iter() {
+        let child = spawn_node(commands, node, model_handles);
+        commands.entity(parent).add_child(child);
+    }
+}
+
+/// Spawns a part, or an assembly with everything in it. Part names have to be checked against the
+/// catalog first
+pub fn spawn_node(
+    commands: &mut Commands,
+    node: &AssemblyNode,
+    model_handles: &ModelHandles,
+) -> Entity {
+    match node {
+        AssemblyNode::Part {
+            part_name,
+            transform,
+        } => {
+            let mut transform = tuple_to_transform(*transform);
+            transform.scale *= import_scale(part_name);
+            commands
+                .spawn((
+                    SceneBundle {
+                        scene: get_model_handle(part_name.clone(), model_handles.clone()),
+                        transform,
+                        ..default()
+                    },
+                    Part,
+                    PartName(part_name.clone()),
+                    PendingConstraints,
+                ))
+                .id()
+        }
+        AssemblyNode::Assembly {
+            name,
+            transform,
+            children,
+        } => {
+            let assembly = commands
+                .spawn((
+                    SpatialBundle::from_transform(tuple_to_transform(*transform)),
+                    Assembly { name: name.clone() },
+                ))
+                .id();
+            spawn_nodes(commands, assembly, children, model_handles);
+            assembly
+        }
+    }
+}
@@ -0,0 +1,228 @@
+use std::fs::File;
+use std::io::{Read, Write};
+
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_mod_raycast::prelude::*;
+
+use crate::{
+    actions::{Action, ActionList},
+    keybinds::{ActionState, InputAction},
+    move_objects::CurrentlyMoving,
+    placing::{placing_point, CurrentlyPlacing, Part, PartName, PlacingState},
+    saving::file_name,
+    ui::editor::handle::ModelHandles,
+};
+
+use super::{
+    assembly_to_file, spawn_assembly_file, Assembly, AssemblyFile, UngroupEvent, ASSEMBLIES_FOLDER,
+};
+
+pub struct AssemblyUiPlugin;
+
+impl Plugin for AssemblyUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<AssemblyLibrary>().add_systems(
+            Update,
+            (
+                assembly_window,
+                toggle_assembly_library,
+                assembly_library_window,
+                place_assembly.run_if(in_state(PlacingState::Placing)),
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+struct AssemblyLibrary {
+    open: bool,
+    files: Vec<String>,
+    /// What happened with the last assembly that was saved or inserted
+    status: Option<String>,
+}
+
+/// Marks an inserted assembly that is still following the cursor
+#[derive(Component)]
+struct PlacingAssembly;
+
+/// Shows the name of the selected assembly and lets it be saved or ungrouped
+fn assembly_window(
+    mut contexts: EguiContexts,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    mut assembly_query: Query<(&mut Assembly, &Transform, Option<&Children>)>,
+    part_query: Query<(&PartName, &Transform)>,
+    mut ungroup_events: EventWriter<UngroupEvent>,
+    mut library: ResMut<AssemblyLibrary>,
+) {
+    let Ok(entity) = selected_query.get_single() else {
+        return;
+    };
+    let Ok((assembly, _, _)) = assembly_query.get(entity) else {
+        return;
+    };
+    let mut name = assembly.name.clone();
+    let mut save = false;
+    egui::Window::new("Assembly")
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Name");
+                ui.text_edit_singleline(&mut name);
+            });
+            ui.horizontal(|ui| {
+                save = ui.button("Save to file").clicked();
+                if ui.button("Ungroup").clicked() {
+                    ungroup_events.send(UngroupEvent(entity));
+                }
+            });
+            if let Some(status) = &library.status {
+                ui.label(status);
+            }
+        });
+
+    if name != assembly.name {
+        assembly_query.get_mut(entity).unwrap().0.name = name;
+    }
+    if save {
+        if let Some(file) = assembly_to_file(entity, &assembly_query.to_readonly(), &part_query) {
+            library.status = Some(match save_assembly_file(&file) {
+                Ok(path) => format!("Saved to {path}"),
+                Err(error) => format!("Couldn't save: {error}"),
+            });
+        }
+    }
+}
+
+/// Returns where the assembly was saved
+fn save_assembly_file(file: &AssemblyFile) -> Result<String, String> {
+    std::fs::create_dir_all(ASSEMBLIES_FOLDER)
+        .map_err(|error| format!("Couldn't create the {ASSEMBLIES_FOLDER} folder: {error}"))?;
+    let name = match file_name(file.name.trim()) {
+        name if name.is_empty() => String::from("assembly"),
+        name => name,
+    };
+    let path = format!("{ASSEMBLIES_FOLDER}/{name}.ron");
+    let contents = ron::ser::to_string(file).map_err(|error| error.to_string())?;
+    File::create(&path)
+        .and_then(|mut file| file.write_all(contents.as_bytes()))
+        .map_err(|error| format!("{path}: {error}"))?;
+    Ok(path)
+}
+
+fn load_assembly_file(path: &str) -> Result<AssemblyFile, String> {
+    let mut contents = String::new();
+    File::open(path)
+        .and_then(|mut file| file.read_to_string(&mut contents))
+        .map_err(|error| error.to_string())?;
+    let file: AssemblyFile = ron::from_str(&contents).map_err(|error| error.to_string())?;
+    file.check_parts()?;
+    Ok(file)
+}
+
+/// Opens the list of saved assemblies
+fn toggle_assembly_library(actions: Res<ActionState>, mut library: ResMut<AssemblyLibrary>) {
+    if !actions.just_pressed(InputAction::InsertAssembly) {
+        return;
+    }
+    library.open = !library.open;
+    if library.open {
+        library.files = std::fs::read_dir(ASSEMBLIES_FOLDER)
+            .map(|entries| {
+                entries
+                    .filter_map(|entry| entry.ok())
+                    .map(|entry| entry.path())
+                    .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+                    .map(|path| path.to_string_lossy().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        library.files.sort();
+    }
+}
+
+fn assembly_library_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut library: ResMut<AssemblyLibrary>,
+    model_handles: Res<ModelHandles>,
+    mut placing_state: ResMut<NextState<PlacingState>>,
+    placing_query: Query<Entity, With<CurrentlyPlacing>>,
+) {
+    if !library.open {
+        return;
+    }
+    let mut open = true;
+    let mut insert = None;
+    egui::Window::new("Assemblies")
+        .open(&mut open)
+        .show(contexts.ctx_mut(), |ui| {
+            if library.files.is_empty() {
+                ui.label(format!("No assemblies saved in {ASSEMBLIES_FOLDER}/ yet"));
+            }
+            if let Some(status) = &library.status {
+                ui.label(status);
+            }
+            for path in library.files.iter() {
+                ui.horizontal(|ui| {
+                    ui.label(path.as_str());
+                    if ui.button("Insert").clicked() {
+                        insert = Some(path.clone());
+                    }
+                });
+            }
+        });
+    library.open = open;
+
+    let Some(path) = insert else {
+        return;
+    };
+    let file = match load_assembly_file(&path) {
+        Ok(file) => file,
+        Err(error) => {
+            library.status = Some(format!("Couldn't insert {path}: {error}"));
+            return;
+        }
+    };
+    for entity in placing_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    // Out of the camera's view until the cursor moves
+    let transform = Transform::from_xyz(0.0, -10000.0, 0.0);
+    let assembly = spawn_assembly_file(&mut commands, &file, transform, &model_handles);
+    commands
+        .entity(assembly)
+        .insert((CurrentlyPlacing, PlacingAssembly));
+    placing_state.set(PlacingState::Placing);
+    library.open = false;
+    library.status = None;
+}
+
+fn place_assembly(
+    mut commands: Commands,
+    mut placing_query: Query<(Entity, &mut Transform, &Assembly), With<PlacingAssembly>>,
+    placed_query: Query<Entity, With<Part>>,
+    cursor_ray: Res<CursorRay>,
+    mut raycast: Raycast,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut placing_state: ResMut<NextState<PlacingState>>,
+    mut action_list: ResMut<ActionList>,
+) {
+    for (entity, mut transform, assembly) in placing_query.iter_mut() {
+        if let Some(cursor_ray) = **cursor_ray {
+            transform.translation = placing_point(cursor_ray, &mut raycast, &|filter_entity| {
+                placed_query.contains(filter_entity)
+            });
+        }
+        if mouse.just_pressed(MouseButton::Left) {
+            commands
+                .entity(entity)
+                .remove::<(CurrentlyPlacing, PlacingAssembly)>();
+            action_list
+                .0
+                .push(Action::Placed(assembly.name.clone(), entity));
+            placing_state.set(PlacingState::NotPlacing);
+        }
+    }
+}
@@ -0,0 +1,377 @@
+use std::collections::BTreeMap;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    assembly::AssemblyNode,
+    catalog::{find_by_scene_path, PartCategory},
+    pattern::PatternKind,
+    placing::{CurrentlyPlacing, PartName},
+    saving::SAVE_FILE,
+};
+
+pub struct BomPlugin;
+
+impl Plugin for BomPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportBom>().add_systems(Update, export_bom);
+    }
+}
+
+/// Writes the parts list of the open project as `bom.csv`, `bom.md` and `bom.json`
+#[derive(Event, Debug)]
+pub struct ExportBom;
+
+/// One row of the bill of materials
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BomLine {
+    pub category: PartCategory,
+    /// Catalog ID, which is what the parts are counted by
+    pub id: String,
+    pub name: String,
+    pub part_number: Option<String>,
+    pub quantity: usize,
+    /// In dollars
+    pub unit_cost: Option<f32>,
+}
+
+impl BomLine {
+    /// What the line can be matched by in an inventory: its part number, catalog ID or name
+    pub fn stock_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = Vec::new();
+        for key in [
+            self.part_number.as_deref(),
+            Some(self.id.as_str()),
+            Some(self.name.as_str()),
+        ]
+        .into_iter()
+        .flatten()
+        .map(stock_key)
+        {
+            if !key.is_empty() && !keys.contains(&key) {
+                keys.push(key);
+            }
+        }
+        keys
+    }
+
+    pub fn total_cost(&self) -> Option<f32> {
+        self.unit_cost.map(|cost| cost * self.quantity as f32)
+    }
+}
+
+/// Inventories are matched without caring about case or surrounding spaces
+pub fn stock_key(text: &str) -> String {
+    text.trim().to_lowercase()
+}
+
+/// The catalog ID a placed part is counted under, or its path if it isn't in the catalog
+pub fn part_id(part_path: &str) -> &str {
+    find_by_scene_path(part_path).map_or(part_path, |entry| entry.id)
+}
+
+/// Counts parts by catalog ID, grouped by category. Paths that aren't in the catalog are listed
+/// as they are under "Other"
+pub fn bill_of_materials<'a>(part_paths: impl IntoIterator<Item = &'a str>) -> Vec<BomLine> {
+    let mut lines: BTreeMap<(PartCategory, String), BomLine> = BTreeMap::new();
+    for path in part_paths {
+        let entry = find_by_scene_path(path);
+        let category = entry.map_or(PartCategory::Other, |entry| entry.category);
+        let id = part_id(path).to_string();
+        lines
+            .entry((category, id.clone()))
+            .or_insert_with(|| BomLine {
+                category,
+                id,
+                name: entry.map_or(path, |entry| entry.name).to_string(),
+                part_number: entry.and_then(|entry| entry.part_number).map(String::from),
+                quantity: 0,
+                unit_cost: entry.and_then(|entry| entry.cost),
+            })
+            .quantity += 1;
+    }
+    lines.into_values().collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BomFormat {
+    Csv,
+    Markdown,
+    Json,
+}
+
+impl BomFormat {
+    pub const ALL: [BomFormat; 3] = [Self::Csv, Self::Markdown, Self::Json];
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Csv => "csv",
+            Self::Markdown => "md",
+            Self::Json => "json",
+        }
+    }
+
+    pub fn from_extension(extension: &str) -> Option<BomFormat> {
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension().eq_ignore_ascii_case(extension))
+    }
+
+    pub fn render(&self, lines: &[BomLine]) -> String {
+        let money = |cost: Option<f32>| cost.map(|cost| format!("{cost:.2}")).unwrap_or_default();
+        // Lines without a price are left out of the total, and flagged if anything has a price
+        let total: f32 = lines.iter().filter_map(|line| line.total_cost()).sum();
+        let unpriced = lines.iter().filter(|line| line.unit_cost.is_none()).count();
+        let priced = unpriced < lines.len();
+        let flagged_money = |cost: Option<f32>| match cost {
+            None if priced => String::from("?"),
+            cost => money(cost),
+        };
+        match self {
+            Self::Csv => {
+                let mut text = String::from("Category,Part number,Name,Quantity,Unit cost,Cost\n");
+                for line in lines {
+                    text.push_str(&format!(
+                        "{},{},{},{},{},{}\n",
+                        csv_field(line.category.label()),
+                        csv_field(line.part_number.as_deref().unwrap_or_default()),
+                        csv_field(&line.name),
+                        line.quantity,
+                        money(line.unit_cost),
+                        money(line.total_cost()),
+                    ));
+                }
+                text
+            }
+            Self::Markdown => {
+                let mut text = String::from("# Bill of materials\n");
+                let mut category = None;
+                for line in lines {
+                    if category != Some(line.category) {
+                        category = Some(line.category);
+                        text.push_str(&format!("\n## {}\n\n", line.category.label()));
+                        text.push_str("| Part number | Name | Quantity | Unit cost | Cost |\n");
+                        text.push_str("|---|---|---:|---:|---:|\n");
+                    }
+                    text.push_str(&format!(
+                        "| {} | {} | {} | {} | {} |\n",
+                        markdown_cell(line.part_number.as_deref().unwrap_or_default()),
+                        markdown_cell(&line.name),
+                        line.quantity,
+                        flagged_money(line.unit_cost),
+                        flagged_money(line.total_cost()),
+                    ));
+                }
+                if priced {
+                    text.push_str(&format!("\n**Total: ${total:.2}**\n"));
+                    if unpriced > 0 {
+                        text.push_str(&format!(
+                            "\nNot counting the {unpriced} lines without a price, marked with ?\n"
+                        ));
+                    }
+                }
+                text
+            }
+            Self::Json => serde_json::to_string_pretty(lines).unwrap(),
+        }
+    }
+}
+
+/// Escapes what would end a table cell early
+fn markdown_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Quotes a CSV field if it has anything in it that would break the row up
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_string()
+    }
+}
+
+fn export_bom(
+    mut export_events: EventReader<ExportBom>,
+    part_query: Query<&PartName, Without<CurrentlyPlacing>>,
+) {
+    if export_events.read().count() == 0 {
+        return;
+    }
+    let lines = bill_of_materials(part_query.iter().map(|part_name| part_name.0.as_str()));
+    for format in BomFormat::ALL {
+        let path = format!("bom.{}", format.extension());
+        match std::fs::write(&path, format.render(&lines)) {
+            Ok(()) => println!("Wrote the bill of materials to {path}"),
+            Err(error) => println!("Couldn't write {path}: {error}"),
+        }
+    }
+}
+
+/// Only the part paths are read from the project file
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProjectParts {
+    parts: Vec<ProjectPart>,
+    assemblies: Vec<AssemblyNode>,
+    patterns: Vec<ProjectPattern>,
+}
+
+/// Pattern copies aren't in the project file, so they're counted from their sources
+#[derive(Deserialize)]
+struct ProjectPattern {
+    /// Indices into `parts`
+    sources: Vec<usize>,
+    kind: PatternKind,
+}
+
+#[derive(Deserialize)]
+struct ProjectPart {
+    part_name: String,
+}
+
+/// `vaic --bom <file> [project]` writes the bill of materials of a saved project without opening
+/// the editor, in the format that goes with the file's extension. Returns whether it ran
+pub fn run_from_command_line() -> bool {
+    let mut args = std::env::args().skip(1);
+    if args.next().as_deref() != Some("--bom") {
+        return false;
+    }
+    let Some(output) = args.next() else {
+        eprintln!("Usage: vaic --bom <bom.csv|bom.md|bom.json> [project file]");
+        return true;
+    };
+    let project = args.next().unwrap_or_else(|| SAVE_FILE.to_string());
+
+    let Some(format) = std::path::Path::new(&output)
+        .extension()
+        .and_then(|extension| BomFormat::from_extension(&extension.to_string_lossy()))
+    else {
+        eprintln!("Can't tell what format {output} should be, use .csv, .md or .json");
+        return true;
+    };
+    let project_parts = match std::fs::read_to_string(&project)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            ron::from_str::<ProjectParts>(&contents).map_err(|error| error.to_string())
+        }) {
+        Ok(project_parts) => project_parts,
+        Err(error) => {
+            eprintln!("Couldn't read {project}: {error}");
+            return true;
+        }
+    };
+    let in_assemblies = project_parts
+        .assemblies
+        .iter()
+        .flat_map(|node| node.part_names());
+    let copies = project_parts.patterns.iter().flat_map(|pattern| {
+        let parts = &project_parts.parts;
+        pattern
+            .sources
+            .iter()
+            .filter_map(|index| parts.get(*index))
+            .flat_map(|part| {
+                std::iter::repeat(part.part_name.as_str())
+                    .take(pattern.kind.count().saturating_sub(1) as usize)
+            })
+    });
+    let lines = bill_of_materials(
+        project_parts
+            .parts
+            .iter()
+            .map(|part| part.part_name.as_str())
+            .chain(in_assemblies)
+            .chain(copies),
+    );
+    match std::fs::write(&output, format.render(&lines)) {
+        Ok(()) => println!("Wrote the bill of materials to {output}"),
+        Err(error) => eprintln!("Couldn't write {output}: {error}"),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(name: &str, part_number: Option<&str>, quantity: usize, cost: Option<f32>) -> BomLine {
+        BomLine {
+            category: PartCategory::Structure,
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            part_number: part_number.map(String::from),
+            quantity,
+            unit_cost: cost,
+        }
+    }
+
+    #[test]
+    fn counts_parts_by_catalog_id() {
+        let lines = bill_of_materials([
+            "models/cube.glb#Scene0",
+            "models/c-channel-1x2x1x1.glb#Scene0",
+            "models/cube.glb#Scene0",
+            "models/missing.glb#Scene0",
+        ]);
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[0].id, "c-channel-1x2x1x1");
+        assert_eq!(lines[0].name, "2x1 C-Channel");
+        assert_eq!(lines[0].category, PartCategory::Structure);
+        let cube = lines.iter().find(|line| line.name == "Cube").unwrap();
+        assert_eq!(cube.quantity, 2);
+        assert!(lines
+            .iter()
+            .any(|line| line.name == "models/missing.glb#Scene0"
+                && line.category == PartCategory::Other));
+    }
+
+    #[test]
+    fn stock_keys_ignore_case_and_spaces() {
+        let mut bolt = line(" Bolt ", Some(" 276-1234 "), 1, None);
+        assert_eq!(bolt.stock_keys(), ["276-1234", "bolt"]);
+        bolt.part_number = Some("  ".to_string());
+        assert_eq!(bolt.stock_keys(), ["bolt"]);
+        let channel = &bill_of_materials(["models/c-channel-1x2x1x1.glb#Scene0"])[0];
+        assert_eq!(channel.stock_keys(), ["c-channel-1x2x1x1", "2x1 c-channel"]);
+    }
+
+    #[test]
+    fn quotes_csv_fields() {
+        let lines = [line("Bolt, 8-32 \"long\"", Some("276-1"), 4, Some(0.5))];
+        assert_eq!(
+            BomFormat::Csv.render(&lines),
+            "Category,Part number,Name,Quantity,Unit cost,Cost\n\
+             Structure,276-1,\"Bolt, 8-32 \"\"long\"\"\",4,0.50,2.00\n"
+        );
+    }
+
+    #[test]
+    fn escapes_markdown_cells() {
+        let lines = [line("A | B\nC", None, 1, None)];
+        let text = BomFormat::Markdown.render(&lines);
+        assert!(text.contains("|  | A \\| B C | 1 |  |  |\n"));
+        // Nothing has a price, so there's no total
+        assert!(!text.contains("Total"));
+    }
+
+    #[test]
+    fn totals_the_priced_lines() {
+        let lines = [
+            line("Channel", None, 2, Some(3.0)),
+            line("Mystery", None, 5, None),
+        ];
+        let text = BomFormat::Markdown.render(&lines);
+        assert!(text.contains("| Mystery | 5 | ? | ? |"));
+        assert!(text.contains("**Total: $6.00**"));
+        assert!(text.contains("Not counting the 1 lines without a price"));
+    }
+
+    #[test]
+    fn recognizes_extensions() {
+        assert_eq!(BomFormat::from_extension("MD"), Some(BomFormat::Markdown));
+        assert_eq!(BomFormat::from_extension("csv"), Some(BomFormat::Csv));
+        assert_eq!(BomFormat::from_extension("txt"), None);
+    }
+}
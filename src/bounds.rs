@@ -0,0 +1,89 @@
+use bevy::{
+    prelude::*,
+    render::{
+        mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+        primitives::Aabb,
+    },
+};
+
+use crate::constraints::ConstrainComponent;
+
+/// Meshes that make up what a part looks like (constraint markers aren't part of the shape)
+pub type BoundsQuery<'w, 's> =
+    Query<'w, 's, (&'static Aabb, &'static GlobalTransform), Without<ConstrainComponent>>;
+
+/// Meshes of a part along with where they are, for working with the actual shape
+pub type MeshQuery<'w, 's> =
+    Query<'w, 's, (&'static Handle<Mesh>, &'static GlobalTransform), Without<ConstrainComponent>>;
+
+/// World space bounding box (min, max) of every mesh under `entity`
+pub fn world_bounds(
+    entity: Entity,
+    children_query: &Query<&Children>,
+    aabb_query: &BoundsQuery,
+) -> Option<(Vec3, Vec3)> {
+    let mut bounds: Option<(Vec3, Vec3)> = None;
+    for descendant in std::iter::once(entity).chain(children_query.iter_descendants(entity)) {
+        let Ok((aabb, global_transform)) = aabb_query.get(descendant) else {
+            continue;
+        };
+        let center = Vec3::from(aabb.center);
+        let half_extents = Vec3::from(aabb.half_extents);
+        for corner in 0..8 {
+            let sign = Vec3::new(
+                if corner & 1 == 0 { -1.0 } else { 1.0 },
+                if corner & 2 == 0 { -1.0 } else { 1.0 },
+                if corner & 4 == 0 { -1.0 } else { 1.0 },
+            );
+            let point = global_transform.transform_point(center + half_extents * sign);
+            bounds = Some(match bounds {
+                Some((min, max)) => (min.min(point), max.max(point)),
+                None => (point, point),
+            });
+        }
+    }
+    bounds
+}
+
+/// Every triangle of the meshes under `entity`, in world space
+pub fn world_triangles(
+    entity: Entity,
+    children_query: &Query<&Children>,
+    mesh_query: &MeshQuery,
+    meshes: &Assets<Mesh>,
+) -> Vec<[Vec3; 3]> {
+    let mut triangles = Vec::new();
+    for descendant in std::iter::once(entity).chain(children_query.iter_descendants(entity)) {
+        let Ok((handle, global_transform)) = mesh_query.get(descendant) else {
+            continue;
+        };
+        let Some(mesh) = meshes.get(handle) else {
+            continue;
+        };
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            continue;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            continue;
+        };
+        let positions: Vec<Vec3> = positions
+            .iter()
+            .map(|position| global_transform.transform_point(Vec3::from(*position)))
+            .collect();
+        let indices: Vec<usize> = match mesh.indices() {
+            Some(Indices::U16(indices)) => indices.iter().map(|index| *index as usize).collect(),
+            Some(Indices::U32(indices)) => indices.iter().map(|index| *index as usize).collect(),
+            None => (0..positions.len()).collect(),
+        };
+        triangles.extend(indices.chunks_exact(3).filter_map(|triangle| {
+            Some([
+                *positions.get(triangle[0])?,
+                *positions.get(triangle[1])?,
+                *positions.get(triangle[2])?,
+            ])
+        }));
+    }
+    triangles
+}
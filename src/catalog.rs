@@ -0,0 +1,166 @@
+use serde::{Deserialize, Serialize};
+
+use crate::units::{HOLE_PITCH, MILLIMETER};
+
+/// What kind of part something is, for rule checks and parts lists
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum PartCategory {
+    Structure,
+    Motion,
+    Hardware,
+    Motor,
+    Pneumatic,
+    Electronics,
+    Other,
+}
+
+impl PartCategory {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Structure => "Structure",
+            Self::Motion => "Motion",
+            Self::Hardware => "Hardware",
+            Self::Motor => "Motors",
+            Self::Pneumatic => "Pneumatics",
+            Self::Electronics => "Electronics",
+            Self::Other => "Other",
+        }
+    }
+}
+
+/// What a part is made of, for estimating the mass of parts that don't list one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Material {
+    Steel,
+    Plastic,
+}
+
+impl Material {
+    /// In pounds per cubic inch
+    pub fn density(&self) -> f32 {
+        match self {
+            Self::Steel => 0.284,
+            Self::Plastic => 0.043,
+        }
+    }
+}
+
+/// Everything VAIC knows about a part that can be placed
+#[derive(Debug, Clone, Copy)]
+pub struct CatalogEntry {
+    /// Name shown in the UI
+    pub name: &'static str,
+    /// Stays the same when the name shown in the UI changes, parts are counted by it
+    pub id: &'static str,
+    /// File name of the model inside `assets/models`
+    pub model: &'static str,
+    /// Whether the part is symmetric about its own YZ plane, which makes a reflected placement of
+    /// it a true mirror image. None of the parts are handed, so there's nothing to swap them for
+    pub symmetric: bool,
+    /// Inches per unit of length in the model file, e.g. [`MILLIMETER`] (1/25.4) for models made in
+    /// millimeters
+    pub import_scale: f32,
+    pub category: PartCategory,
+    /// Whether this is a real VEX part, as opposed to a placeholder model
+    pub vex_part: bool,
+    pub material: Material,
+    /// In pounds, from the part's spec sheet. Without it the mass comes from the model's volume
+    pub mass: Option<f32>,
+    /// Vendor part number, for ordering
+    pub part_number: Option<&'static str>,
+    /// Price of one, in dollars
+    pub cost: Option<f32>,
+}
+
+impl CatalogEntry {
+    /// Path used for `PartName` and to look up the model's scene handle
+    pub fn scene_path(&self) -> String {
+        format!("models/{}#Scene0", self.model)
+    }
+}
+
+// We do a little hardcoding (until we load this from a file)
+pub const CATALOG: &[CatalogEntry] = &[
+    CatalogEntry {
+        name: "Duck",
+        id: "duck",
+        model: "low_poly_duck.glb",
+        symmetric: false,
+        // Placeholders don't have a real size
+        import_scale: 1.0,
+        category: PartCategory::Other,
+        vex_part: false,
+        material: Material::Plastic,
+        mass: None,
+        part_number: None,
+        cost: None,
+    },
+    CatalogEntry {
+        name: "Cube",
+        id: "cube",
+        model: "cube.glb",
+        symmetric: true,
+        import_scale: 1.0,
+        category: PartCategory::Other,
+        vex_part: false,
+        material: Material::Plastic,
+        mass: None,
+        part_number: None,
+        cost: None,
+    },
+    CatalogEntry {
+        name: "Penguin",
+        id: "penguin",
+        model: "penguin.glb",
+        symmetric: false,
+        import_scale: 1.0,
+        category: PartCategory::Other,
+        vex_part: false,
+        material: Material::Plastic,
+        mass: None,
+        part_number: None,
+        cost: None,
+    },
+    CatalogEntry {
+        name: "2x25 C-Channel",
+        id: "c-channel-1x2x1x25",
+        model: "c-channel-1x2x1x25.glb",
+        symmetric: true,
+        // Modeled in millimeters, and scaled down by 20 in the file
+        import_scale: 20.0 * MILLIMETER,
+        category: PartCategory::Structure,
+        vex_part: true,
+        material: Material::Steel,
+        mass: Some(0.25),
+        part_number: None,
+        cost: None,
+    },
+    CatalogEntry {
+        name: "2x1 C-Channel",
+        id: "c-channel-1x2x1x1",
+        model: "c-channel-1x2x1x1.glb",
+        symmetric: true,
+        // The holes in the model are 0.58 apart
+        import_scale: HOLE_PITCH / 0.58,
+        category: PartCategory::Structure,
+        vex_part: true,
+        material: Material::Steel,
+        mass: Some(0.01),
+        part_number: None,
+        cost: None,
+    },
+];
+
+pub fn find_by_name(name: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|entry| entry.name == name)
+}
+
+pub fn find_by_scene_path(path: &str) -> Option<&'static CatalogEntry> {
+    CATALOG.iter().find(|entry| entry.scene_path() == path)
+}
+
+/// [`CatalogEntry::import_scale`] of the part with this scene path, parts that aren't in the
+/// catalog aren't scaled
+pub fn import_scale(path: &str) -> f32 {
+    find_by_scene_path(path).map_or(1.0, |entry| entry.import_scale)
+}
@@ -1,6 +1,7 @@
 use std::f32::consts::PI;
 
 use crate::placing::*;
+use crate::units::HOLE_PITCH;
 // use crate::ui::editor::Models;
 // use bevy::gltf::Gltf;
 // use bevy::gltf::GltfMesh;
@@ -12,6 +13,22 @@ use super::ConstraintData;
 #[derive(Event)]
 pub struct AddConstraintsEvent(pub Entity);
 
+/// Parts spawned by something other than placing get their constraints a frame after they're
+/// spawned, once their `PartName` is queryable
+#[derive(Component)]
+pub struct PendingConstraints;
+
+pub fn add_pending_constraints(
+    mut commands: Commands,
+    pending_query: Query<Entity, With<PendingConstraints>>,
+    mut add_constraints_event: EventWriter<AddConstraintsEvent>,
+) {
+    for entity in pending_query.iter() {
+        add_constraints_event.send(AddConstraintsEvent(entity));
+        commands.entity(entity).remove::<PendingConstraints>();
+    }
+}
+
 pub fn add_constraints_event(
     mut commands: Commands,
     part_query: Query<&PartName, With<Part>>,
@@ -26,8 +43,10 @@ pub fn add_constraints_event(
     for event in add_constraints_event_reader.read() {
         let entity = event.0;
         let part_name: &PartName = part_query.get(entity).unwrap();
-
-        let name = crate::ui::editor::part_selector::reverse_model_name(part_name.0.clone());
+        let Some(entry) = crate::catalog::find_by_scene_path(&part_name.0) else {
+            continue;
+        };
+        let name = entry.name.to_string();
 
         // let folder = models.folder.clone();
         let constraints: Vec<ConstraintData> = get_constraint_data(
@@ -53,7 +72,13 @@ pub fn add_constraints_event(
                         base_color: Color::RED,
                         ..default()
                     }),
-                    transform: constraint.transform,
+                    // The part is scaled from model units to inches, so the offsets (which are in
+                    // inches) and the marker are scaled back
+                    transform: Transform {
+                        translation: constraint.transform.translation / entry.import_scale,
+                        scale: Vec3::splat(1.0 / entry.import_scale),
+                        ..constraint.transform
+                    },
                     ..default()
                 })
                 .insert(ConstrainComponent {})
@@ -103,63 +128,41 @@ fn get_constraint_data(
     // }
     // We do a little hardcoding (teehee)
     return match name.as_str() {
-        "2x1 C-Channel" => vec![
-            ConstraintData {
-                transform: Transform::from_translation(Vec3::new(0.29, 0.035, 0.0)),
-                ..default()
-            },
-            ConstraintData {
-                transform: Transform::from_translation(Vec3::new(-0.29, 0.035, 0.0)),
-                ..default()
-            },
-            ConstraintData {
-                transform: Transform::from_translation(Vec3::new(0.0, 0.035, -0.29)),
-            },
-            ConstraintData {
-                transform: Transform {
-                    translation: Vec3::new(-0.55, 0.365, 0.0),
-                    rotation: Quat::from_rotation_z(PI / 2.0),
-                    ..default()
-                },
-            },
-            ConstraintData {
-                transform: Transform {
-                    translation: Vec3::new(0.55, 0.365, 0.0),
-                    rotation: Quat::from_rotation_z(PI / 2.0),
-                    ..default()
-                },
-            },
-        ],
-        "2x25 C-Channel" => vec![
-            ConstraintData {
-                transform: Transform::from_translation(Vec3::new(0.29, 0.035, 0.0)),
-                ..default()
-            },
-            ConstraintData {
-                transform: Transform::from_translation(Vec3::new(-0.29, 0.035, 0.0)),
-                ..default()
-            },
-            ConstraintData {
-                transform: Transform::from_translation(Vec3::new(0.0, 0.035, -0.29)),
-            },
-            ConstraintData {
-                transform: Transform {
-                    translation: Vec3::new(-0.55, 0.365, 0.0),
-                    rotation: Quat::from_rotation_z(PI / 2.0),
-                    ..default()
-                },
-            },
-            ConstraintData {
-                transform: Transform {
-                    translation: Vec3::new(0.55, 0.365, 0.0),
-                    rotation: Quat::from_rotation_z(PI / 2.0),
-                    ..default()
-                },
-            },
-        ],
+        "2x1 C-Channel" | "2x25 C-Channel" => c_channel_holes(),
         _ => {
             println!("{name} has no constraints!");
             vec![]
         }
     };
 }
+
+/// VEX steel is about this thick (in inches), holes sit in the middle of the sheet
+const SHEET_THICKNESS: f32 = 0.06;
+
+/// Holes of a C-channel in inches from the part's origin, which is in the middle of the bottom:
+/// two holes along the width of the base, one along its length and one in the middle of each side
+fn c_channel_holes() -> Vec<ConstraintData> {
+    let base_height = SHEET_THICKNESS / 2.0;
+    let side_offset = HOLE_PITCH - SHEET_THICKNESS / 2.0;
+    let side_height = HOLE_PITCH / 2.0 + SHEET_THICKNESS;
+    let side_rotation = Quat::from_rotation_z(PI / 2.0);
+    vec![
+        ConstraintData {
+            transform: Transform::from_xyz(HOLE_PITCH / 2.0, base_height, 0.0),
+        },
+        ConstraintData {
+            transform: Transform::from_xyz(-HOLE_PITCH / 2.0, base_height, 0.0),
+        },
+        ConstraintData {
+            transform: Transform::from_xyz(0.0, base_height, -HOLE_PITCH / 2.0),
+        },
+        ConstraintData {
+            transform: Transform::from_xyz(-side_offset, side_height, 0.0)
+                .with_rotation(side_rotation),
+        },
+        ConstraintData {
+            transform: Transform::from_xyz(side_offset, side_height, 0.0)
+                .with_rotation(side_rotation),
+        },
+    ]
+}
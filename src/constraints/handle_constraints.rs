@@ -2,6 +2,7 @@ use bevy::prelude::*;
 use bevy_mod_raycast::prelude::*;
 
 use crate::actions::ActionList;
+use crate::placing::Part;
 
 use super::{ui::CurrentConstraintOperation, ConstrainComponent, ConstraintData};
 
@@ -12,10 +13,28 @@ pub struct ConstraintEvent {
     pub parents: [Entity; 2],
 }
 
+/// Every constraint that currently holds two parts together
+#[derive(Resource, Default, Debug)]
+pub struct Mates(pub Vec<ConstraintEvent>);
+
+impl Mates {
+    /// Removes the most recent mate between the same two parts as `event`
+    pub fn remove(&mut self, event: &ConstraintEvent) {
+        if let Some(index) = self
+            .0
+            .iter()
+            .rposition(|mate| mate.parents == event.parents)
+        {
+            self.0.remove(index);
+        }
+    }
+}
+
 pub fn handle_constraint_event(
     mut events: EventReader<ConstraintEvent>,
     mut transform_query: Query<&mut Transform, With<crate::placing::Part>>,
     mut action_list: ResMut<ActionList>,
+    mut mates: ResMut<Mates>,
 ) {
     for event in events.read() {
         if event.parents[0].index() == event.parents[1].index() {
@@ -26,6 +45,7 @@ pub fn handle_constraint_event(
         let other = event.constraints[1].transform;
         *transform = constrain_to(transform.clone(), curr, other);
         action_list.0.push(event.clone().into());
+        mates.0.push(*event);
     }
 }
 
@@ -121,3 +141,10 @@ pub fn select_constraints(
         }
     }
 }
+
+/// Mates can't hold onto parts that were deleted or undone
+pub fn remove_dangling_mates(mut mates: ResMut<Mates>, mut removed_parts: RemovedComponents<Part>) {
+    for entity in removed_parts.read() {
+        mates.0.retain(|mate| !mate.parents.contains(&entity));
+    }
+}
@@ -1,3 +1,4 @@
+use crate::keybinds::{ActionState, InputAction};
 use crate::placing::PlacingState;
 use bevy::prelude::*;
 
@@ -5,12 +6,12 @@ mod ui;
 use ui::ConstraintUiPlugin;
 
 mod add_constraints;
-pub use add_constraints::AddConstraintsEvent;
 use add_constraints::*;
+pub use add_constraints::{AddConstraintsEvent, PendingConstraints};
 
 mod handle_constraints;
-pub use handle_constraints::ConstraintEvent;
 use handle_constraints::*;
+pub use handle_constraints::{ConstraintEvent, Mates};
 
 pub struct ConstraintPlugin;
 
@@ -19,6 +20,7 @@ impl Plugin for ConstraintPlugin {
         app.init_state::<ConstrainState>()
             .add_event::<AddConstraintsEvent>()
             .add_event::<ConstraintEvent>()
+            .init_resource::<Mates>()
             .add_plugins(ConstraintUiPlugin)
             .add_systems(Update, check_for_c)
             .add_systems(
@@ -28,9 +30,14 @@ impl Plugin for ConstraintPlugin {
             )
             .add_systems(OnEnter(ConstrainState::Constraining), show_constraints)
             .add_systems(OnExit(ConstrainState::Constraining), hide_constraints)
+            // Parts can also be spawned outside of placing mode (e.g. patterns)
             .add_systems(
                 Update,
-                add_constraints_event.run_if(in_state(crate::placing::PlacingState::Placing)),
+                (
+                    add_pending_constraints,
+                    add_constraints_event,
+                    remove_dangling_mates,
+                ),
             );
     }
 }
@@ -51,22 +58,22 @@ pub struct ConstraintData {
 }
 
 fn check_for_c(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut placing_state: ResMut<NextState<PlacingState>>,
     mut constrain_state: ResMut<NextState<ConstrainState>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyC) {
+    if actions.just_pressed(InputAction::Constrain) {
         placing_state.set(PlacingState::PlacingDisabled);
         constrain_state.set(ConstrainState::Constraining);
     }
 }
 
 fn exit_constrain(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut placing_state: ResMut<NextState<PlacingState>>,
     mut constrain_state: ResMut<NextState<ConstrainState>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Cancel) {
         placing_state.set(PlacingState::NotPlacing);
         constrain_state.set(ConstrainState::NotConstraining);
     }
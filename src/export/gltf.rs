@@ -0,0 +1,361 @@
+use std::path::Path;
+
+use bevy::{
+    prelude::*,
+    render::mesh::{Indices, PrimitiveTopology, VertexAttributeValues},
+    utils::HashMap,
+};
+use serde_json::{json, Value};
+
+use crate::{
+    assembly::Assembly, catalog::find_by_scene_path, constraints::ConstrainComponent,
+    placing::PartName,
+};
+
+/// glTF lengths are in meters
+const METERS_PER_INCH: f32 = 0.0254;
+
+// Numbers from the glTF spec
+const FLOAT: u32 = 5126;
+const UNSIGNED_INT: u32 = 5125;
+const ARRAY_BUFFER: u32 = 34962;
+const ELEMENT_ARRAY_BUFFER: u32 = 34963;
+
+/// Parts and assemblies, and what's needed to tell them apart and place them
+pub type NodeQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static PartName>,
+        Option<&'static Assembly>,
+        Option<&'static Name>,
+        &'static Transform,
+        &'static GlobalTransform,
+        &'static InheritedVisibility,
+        Option<&'static Children>,
+    ),
+>;
+
+/// Meshes of parts, with their material
+pub type MaterialMeshQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static Handle<Mesh>,
+        Option<&'static Handle<StandardMaterial>>,
+        &'static GlobalTransform,
+    ),
+    Without<ConstrainComponent>,
+>;
+
+/// Builds a glTF document out of parts and assemblies. Every assembly becomes a node with its
+/// parts (and sub-assemblies) under it, and each part a node with its meshes under it. Meshes
+/// shared by parts are only written once
+pub struct GltfBuilder<'a, 'w, 's> {
+    node_query: &'a NodeQuery<'w, 's>,
+    mesh_query: &'a MaterialMeshQuery<'w, 's>,
+    children_query: &'a Query<'w, 's, &'static Children>,
+    meshes: &'a Assets<Mesh>,
+    materials: &'a Assets<StandardMaterial>,
+    nodes: Vec<Value>,
+    /// Nodes at the top, which go under the node that converts the units
+    roots: Vec<usize>,
+    gltf_meshes: Vec<Value>,
+    gltf_materials: Vec<Value>,
+    accessors: Vec<Value>,
+    buffer_views: Vec<Value>,
+    buffer: Vec<u8>,
+    mesh_indices: HashMap<(AssetId<Mesh>, Option<AssetId<StandardMaterial>>), usize>,
+    material_indices: HashMap<AssetId<StandardMaterial>, usize>,
+}
+
+impl<'a, 'w, 's> GltfBuilder<'a, 'w, 's> {
+    pub fn new(
+        node_query: &'a NodeQuery<'w, 's>,
+        mesh_query: &'a MaterialMeshQuery<'w, 's>,
+        children_query: &'a Query<'w, 's, &'static Children>,
+        meshes: &'a Assets<Mesh>,
+        materials: &'a Assets<StandardMaterial>,
+    ) -> Self {
+        Self {
+            node_query,
+            mesh_query,
+            children_query,
+            meshes,
+            materials,
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            gltf_meshes: Vec::new(),
+            gltf_materials: Vec::new(),
+            accessors: Vec::new(),
+            buffer_views: Vec::new(),
+            buffer: Vec::new(),
+            mesh_indices: HashMap::new(),
+            material_indices: HashMap::new(),
+        }
+    }
+
+    /// Adds a part or assembly (and everything in it) where it is in the world, returning whether
+    /// there was anything to add
+    pub fn add_root(&mut self, entity: Entity) -> bool {
+        let Ok((.., global_transform, _, _)) = self.node_query.get(entity) else {
+            return false;
+        };
+        let Some(node) = self.add_node(entity, global_transform.compute_transform()) else {
+            return false;
+        };
+        self.roots.push(node);
+        true
+    }
+
+    fn add_node(&mut self, entity: Entity, transform: Transform) -> Option<usize> {
+        let (part_name, assembly, name, _, global_transform, visibility, children) =
+            self.node_query.get(entity).ok()?;
+        if !visibility.get() {
+            return None;
+        }
+        let mut node = trs(&transform);
+        let mut child_nodes = Vec::new();
+
+        if let Some(part_name) = part_name {
+            node["name"] = json!(name.map(|name| name.to_string()).unwrap_or_else(|| {
+                find_by_scene_path(&part_name.0)
+                    .map_or(part_name.0.clone(), |entry| entry.name.to_string())
+            }));
+            // The model's own nodes are flattened, each mesh is placed relative to the part
+            let to_part = global_transform.affine().inverse();
+            for descendant in self.children_query.iter_descendants(entity) {
+                let Ok((mesh, material, mesh_transform)) = self.mesh_query.get(descendant) else {
+                    continue;
+                };
+                let Some(mesh_index) = self.add_mesh(mesh, material) else {
+                    continue;
+                };
+                let mut mesh_node = trs(&Transform::from_matrix(
+                    (to_part * mesh_transform.affine()).into(),
+                ));
+                mesh_node["mesh"] = json!(mesh_index);
+                self.nodes.push(mesh_node);
+                child_nodes.push(self.nodes.len() - 1);
+            }
+        } else if let Some(assembly) = assembly {
+            node["name"] = json!(assembly.name);
+            for child in children.into_iter().flat_map(|children| children.iter()) {
+                let Ok((_, _, _, child_transform, ..)) = self.node_query.get(*child) else {
+                    continue;
+                };
+                child_nodes.extend(self.add_node(*child, *child_transform));
+            }
+        } else {
+            return None;
+        }
+
+        if !child_nodes.is_empty() {
+            node["children"] = json!(child_nodes);
+        }
+        self.nodes.push(node);
+        Some(self.nodes.len() - 1)
+    }
+
+    fn add_mesh(
+        &mut self,
+        handle: &Handle<Mesh>,
+        material: Option<&Handle<StandardMaterial>>,
+    ) -> Option<usize> {
+        let key = (handle.id(), material.map(|material| material.id()));
+        if let Some(index) = self.mesh_indices.get(&key) {
+            return Some(*index);
+        }
+        let mesh = self.meshes.get(handle)?;
+        if mesh.primitive_topology() != PrimitiveTopology::TriangleList {
+            return None;
+        }
+        let Some(VertexAttributeValues::Float32x3(positions)) =
+            mesh.attribute(Mesh::ATTRIBUTE_POSITION)
+        else {
+            return None;
+        };
+
+        let mut attributes = json!({ "POSITION": self.add_vec3_accessor(positions, true) });
+        if let Some(VertexAttributeValues::Float32x3(normals)) =
+            mesh.attribute(Mesh::ATTRIBUTE_NORMAL)
+        {
+            attributes["NORMAL"] = json!(self.add_vec3_accessor(normals, false));
+        }
+        let mut primitive = json!({ "attributes": attributes });
+        let indices: Option<Vec<u32>> = match mesh.indices() {
+            Some(Indices::U16(indices)) => {
+                Some(indices.iter().map(|index| *index as u32).collect())
+            }
+            Some(Indices::U32(indices)) => Some(indices.clone()),
+            None => None,
+        };
+        if let Some(indices) = indices {
+            let bytes: Vec<u8> = indices
+                .iter()
+                .flat_map(|index| index.to_le_bytes())
+                .collect();
+            let view = self.add_buffer_view(&bytes, ELEMENT_ARRAY_BUFFER);
+            self.accessors.push(json!({
+                "bufferView": view,
+                "componentType": UNSIGNED_INT,
+                "count": indices.len(),
+                "type": "SCALAR",
+            }));
+            primitive["indices"] = json!(self.accessors.len() - 1);
+        }
+        if let Some(material) = material.and_then(|material| self.add_material(material)) {
+            primitive["material"] = json!(material);
+        }
+
+        self.gltf_meshes.push(json!({ "primitives": [primitive] }));
+        let index = self.gltf_meshes.len() - 1;
+        self.mesh_indices.insert(key, index);
+        Some(index)
+    }
+
+    /// Only the base color and how metallic and rough the material is are kept, not textures
+    fn add_material(&mut self, handle: &Handle<StandardMaterial>) -> Option<usize> {
+        if let Some(index) = self.material_indices.get(&handle.id()) {
+            return Some(*index);
+        }
+        let material = self.materials.get(handle)?;
+        let base_color = material.base_color.as_linear_rgba_f32();
+        let mut gltf_material = json!({
+            "name": format!("Material {}", self.gltf_materials.len() + 1),
+            "pbrMetallicRoughness": {
+                "baseColorFactor": base_color,
+                "metallicFactor": material.metallic,
+                "roughnessFactor": material.perceptual_roughness,
+            },
+            "doubleSided": material.double_sided,
+        });
+        if base_color[3] < 1.0 {
+            gltf_material["alphaMode"] = json!("BLEND");
+        }
+        self.gltf_materials.push(gltf_material);
+        let index = self.gltf_materials.len() - 1;
+        self.material_indices.insert(handle.id(), index);
+        Some(index)
+    }
+
+    /// Positions need their bounds written along with them
+    fn add_vec3_accessor(&mut self, values: &[[f32; 3]], bounds: bool) -> usize {
+        let bytes: Vec<u8> = values
+            .iter()
+            .flatten()
+            .flat_map(|value| value.to_le_bytes())
+            .collect();
+        let view = self.add_buffer_view(&bytes, ARRAY_BUFFER);
+        let mut accessor = json!({
+            "bufferView": view,
+            "componentType": FLOAT,
+            "count": values.len(),
+            "type": "VEC3",
+        });
+        if bounds && !values.is_empty() {
+            let (min, max) = values.iter().map(|value| Vec3::from(*value)).fold(
+                (Vec3::splat(f32::MAX), Vec3::splat(f32::MIN)),
+                |(min, max), value| (min.min(value), max.max(value)),
+            );
+            accessor["min"] = json!(min.to_array());
+            accessor["max"] = json!(max.to_array());
+        }
+        self.accessors.push(accessor);
+        self.accessors.len() - 1
+    }
+
+    fn add_buffer_view(&mut self, bytes: &[u8], target: u32) -> usize {
+        // Every view starts on a multiple of 4 bytes
+        while self.buffer.len() % 4 != 0 {
+            self.buffer.push(0);
+        }
+        self.buffer_views.push(json!({
+            "buffer": 0,
+            "byteOffset": self.buffer.len(),
+            "byteLength": bytes.len(),
+            "target": target,
+        }));
+        self.buffer.extend_from_slice(bytes);
+        self.buffer_views.len() - 1
+    }
+
+    /// Writes everything added so far as a `.glb` (`binary`), or as a `.gltf` with the data in a
+    /// `.bin` file next to it. Everything goes under one node that turns inches into meters
+    pub fn write(mut self, path: &Path, binary: bool) -> std::io::Result<()> {
+        self.nodes.push(json!({
+            "name": path.file_stem().map(|stem| stem.to_string_lossy()).unwrap_or_default(),
+            "scale": [METERS_PER_INCH; 3],
+            "children": self.roots,
+        }));
+
+        let mut document = json!({
+            "asset": { "version": "2.0", "generator": "VAIC" },
+            "scene": 0,
+            "scenes": [{ "nodes": [self.nodes.len() - 1] }],
+            "nodes": self.nodes,
+        });
+        if !self.gltf_meshes.is_empty() {
+            document["meshes"] = json!(self.gltf_meshes);
+            document["accessors"] = json!(self.accessors);
+            document["bufferViews"] = json!(self.buffer_views);
+            document["buffers"] = json!([{ "byteLength": self.buffer.len() }]);
+        }
+        if !self.gltf_materials.is_empty() {
+            document["materials"] = json!(self.gltf_materials);
+        }
+
+        if binary {
+            return write_glb(path, &document, &self.buffer);
+        }
+        if !self.buffer.is_empty() {
+            let bin_path = path.with_extension("bin");
+            std::fs::write(&bin_path, &self.buffer)?;
+            document["buffers"][0]["uri"] = json!(bin_path
+                .file_name()
+                .map(|name| name.to_string_lossy())
+                .unwrap_or_default());
+        }
+        std::fs::write(
+            path,
+            serde_json::to_string_pretty(&document).map_err(std::io::Error::other)?,
+        )
+    }
+}
+
+fn trs(transform: &Transform) -> Value {
+    json!({
+        "translation": transform.translation.to_array(),
+        "rotation": transform.rotation.to_array(),
+        "scale": transform.scale.to_array(),
+    })
+}
+
+/// A GLB is a header, then the JSON and the binary data as chunks, each padded to 4 bytes
+fn write_glb(path: &Path, document: &Value, buffer: &[u8]) -> std::io::Result<()> {
+    let mut json = serde_json::to_vec(document).map_err(std::io::Error::other)?;
+    while json.len() % 4 != 0 {
+        json.push(b' ');
+    }
+    let mut binary = buffer.to_vec();
+    while binary.len() % 4 != 0 {
+        binary.push(0);
+    }
+
+    let mut chunks = Vec::new();
+    chunks.extend((json.len() as u32).to_le_bytes());
+    chunks.extend(b"JSON");
+    chunks.extend(json);
+    if !binary.is_empty() {
+        chunks.extend((binary.len() as u32).to_le_bytes());
+        chunks.extend(b"BIN\0");
+        chunks.extend(binary);
+    }
+
+    let mut glb = Vec::with_capacity(12 + chunks.len());
+    glb.extend(b"glTF");
+    glb.extend(2u32.to_le_bytes());
+    glb.extend(((12 + chunks.len()) as u32).to_le_bytes());
+    glb.extend(chunks);
+    std::fs::write(path, glb)
+}
@@ -0,0 +1,289 @@
+use std::path::{Path, PathBuf};
+
+use bevy::prelude::*;
+
+use crate::{
+    assembly::Assembly,
+    bounds::{world_triangles, MeshQuery},
+    catalog::find_by_scene_path,
+    move_objects::CurrentlyMoving,
+    placing::{CurrentlyPlacing, PartName},
+    saving::file_name,
+    units::LengthUnit,
+};
+
+mod gltf;
+use gltf::{GltfBuilder, MaterialMeshQuery, NodeQuery};
+mod obj;
+mod stl;
+mod ui;
+use ui::ExportUiPlugin;
+
+pub struct ExportPlugin;
+
+impl Plugin for ExportPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ExportEvent>()
+            .init_resource::<ExportOptions>()
+            .add_plugins(ExportUiPlugin)
+            .add_systems(
+                Update,
+                (export_parts, export_gltf).run_if(in_state(crate::ui::UIState::Editor)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Stl,
+    Obj,
+    /// glTF with the data in a separate `.bin` file
+    Gltf,
+    /// glTF in one binary file
+    Glb,
+}
+
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 4] = [Self::Stl, Self::Obj, Self::Gltf, Self::Glb];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Stl => "STL",
+            Self::Obj => "OBJ",
+            Self::Gltf => "glTF",
+            Self::Glb => "GLB",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Self::Stl => "stl",
+            Self::Obj => "obj",
+            Self::Gltf => "gltf",
+            Self::Glb => "glb",
+        }
+    }
+
+    /// glTF keeps assemblies, names and colors, and is always in meters
+    pub fn is_gltf(&self) -> bool {
+        matches!(self, Self::Gltf | Self::Glb)
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    /// Unit the exported coordinates are in. The files themselves don't say, so this has to match
+    /// what the other program expects (most slicers want millimeters)
+    pub unit: LengthUnit,
+    /// A file for each part instead of one for everything
+    pub separate_files: bool,
+    /// File name without the extension, or the folder for separate files
+    pub name: String,
+    /// What happened with the last export
+    pub status: Option<String>,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            format: ExportFormat::Stl,
+            unit: LengthUnit::Millimeters,
+            separate_files: false,
+            name: String::from("robot"),
+            status: None,
+        }
+    }
+}
+
+/// Exports the selection, or every visible part if nothing is selected. STL and OBJ files get
+/// every part's triangles in world space, glTF files the parts and assemblies as they are
+#[derive(Event, Debug)]
+pub struct ExportEvent;
+
+/// The triangles of one part, in world space and the export unit
+pub struct ExportedPart {
+    pub name: String,
+    pub triangles: Vec<[Vec3; 3]>,
+}
+
+fn write_parts(parts: &[ExportedPart], options: &ExportOptions) -> std::io::Result<String> {
+    let extension = options.format.extension();
+    let write = |path: &Path, parts: &[ExportedPart]| match options.format {
+        ExportFormat::Stl => stl::write(path, parts),
+        ExportFormat::Obj => obj::write(path, parts),
+        ExportFormat::Gltf | ExportFormat::Glb => unreachable!("glTF has its own exporter"),
+    };
+    if options.separate_files {
+        let folder = PathBuf::from(&options.name);
+        std::fs::create_dir_all(&folder)?;
+        for (index, part) in parts.iter().enumerate() {
+            let path = folder.join(format!(
+                "{} {}.{extension}",
+                index + 1,
+                file_name(&part.name)
+            ));
+            write(&path, std::slice::from_ref(part))?;
+        }
+        Ok(format!(
+            "Exported {} files to {}",
+            parts.len(),
+            folder.display()
+        ))
+    } else {
+        let path = PathBuf::from(format!("{}.{extension}", options.name));
+        write(&path, parts)?;
+        Ok(format!(
+            "Exported {} parts to {}",
+            parts.len(),
+            path.display()
+        ))
+    }
+}
+
+fn export_parts(
+    mut export_events: EventReader<ExportEvent>,
+    mut options: ResMut<ExportOptions>,
+    part_query: Query<
+        (Entity, &PartName, Option<&Name>, &InheritedVisibility),
+        Without<CurrentlyPlacing>,
+    >,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    mesh_query: MeshQuery,
+    meshes: Res<Assets<Mesh>>,
+) {
+    if export_events.read().count() == 0 || options.format.is_gltf() {
+        return;
+    }
+    // Parts inside a selected assembly are exported with it
+    let is_selected = |part: Entity| {
+        selected_query.contains(part)
+            || parent_query
+                .iter_ancestors(part)
+                .any(|ancestor| selected_query.contains(ancestor))
+    };
+    let selection_only = part_query.iter().any(|(entity, ..)| is_selected(entity));
+
+    let scale = options.unit.from_inches(1.0);
+    let parts: Vec<ExportedPart> = part_query
+        .iter()
+        .filter(|(entity, _, _, visibility)| {
+            visibility.get() && (!selection_only || is_selected(*entity))
+        })
+        .map(|(entity, part_name, name, _)| ExportedPart {
+            name: name.map(|name| name.to_string()).unwrap_or_else(|| {
+                find_by_scene_path(&part_name.0)
+                    .map_or(part_name.0.clone(), |entry| entry.name.to_string())
+            }),
+            triangles: world_triangles(entity, &children_query, &mesh_query, &meshes)
+                .into_iter()
+                .map(|triangle| triangle.map(|point| point * scale))
+                .collect(),
+        })
+        .collect();
+
+    options.status = Some(if parts.is_empty() {
+        String::from("Nothing to export")
+    } else {
+        match write_parts(&parts, &options) {
+            Ok(message) => message,
+            Err(error) => format!("Couldn't export: {error}"),
+        }
+    });
+}
+
+/// Parts and assemblies that aren't inside of an assembly
+type TopLevelQuery<'w, 's> = Query<
+    'w,
+    's,
+    Entity,
+    (
+        Or<(With<PartName>, With<Assembly>)>,
+        Without<Parent>,
+        Without<CurrentlyPlacing>,
+    ),
+>;
+
+/// Exports the selected parts and assemblies, or all of them, keeping the assemblies
+fn export_gltf(
+    mut export_events: EventReader<ExportEvent>,
+    mut options: ResMut<ExportOptions>,
+    top_level_query: TopLevelQuery,
+    selected_query: Query<Entity, (With<CurrentlyMoving>, Without<CurrentlyPlacing>)>,
+    node_query: NodeQuery,
+    mesh_query: MaterialMeshQuery,
+    children_query: Query<&Children>,
+    meshes: Res<Assets<Mesh>>,
+    materials: Res<Assets<StandardMaterial>>,
+) {
+    if export_events.read().count() == 0 || !options.format.is_gltf() {
+        return;
+    }
+    let mut roots: Vec<Entity> = selected_query.iter().collect();
+    if roots.is_empty() {
+        roots = top_level_query.iter().collect();
+    }
+    let binary = options.format == ExportFormat::Glb;
+    let extension = options.format.extension();
+    let builder = || {
+        GltfBuilder::new(
+            &node_query,
+            &mesh_query,
+            &children_query,
+            &meshes,
+            &materials,
+        )
+    };
+
+    let result = if options.separate_files {
+        let folder = PathBuf::from(&options.name);
+        std::fs::create_dir_all(&folder).and_then(|_| {
+            let mut count = 0;
+            for root in roots {
+                let mut gltf = builder();
+                if !gltf.add_root(root) {
+                    continue;
+                }
+                count += 1;
+                let name = node_query
+                    .get(root)
+                    .ok()
+                    .and_then(|(part_name, assembly, name, ..)| {
+                        name.map(|name| name.to_string())
+                            .or_else(|| assembly.map(|assembly| assembly.name.clone()))
+                            .or_else(|| {
+                                part_name.map(|part_name| {
+                                    find_by_scene_path(&part_name.0)
+                                        .map_or(part_name.0.clone(), |entry| entry.name.to_string())
+                                })
+                            })
+                    })
+                    .unwrap_or_default();
+                gltf.write(
+                    &folder.join(format!("{count} {}.{extension}", file_name(&name))),
+                    binary,
+                )?;
+            }
+            Ok(format!("Exported {count} files to {}", folder.display()))
+        })
+    } else {
+        let mut gltf = builder();
+        let count = roots
+            .into_iter()
+            .filter(|root| gltf.add_root(*root))
+            .count();
+        let path = PathBuf::from(format!("{}.{extension}", options.name));
+        gltf.write(&path, binary).map(|_| {
+            format!(
+                "Exported {count} parts and assemblies to {}",
+                path.display()
+            )
+        })
+    };
+    options.status = Some(match result {
+        Ok(message) => message,
+        Err(error) => format!("Couldn't export: {error}"),
+    });
+}
@@ -0,0 +1,36 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use super::ExportedPart;
+
+/// Writes a Wavefront OBJ with an object for each part, so they can still be told apart in other
+/// programs. OBJ files are y up like VAIC, so the coordinates are written as they are
+pub fn write(path: &Path, parts: &[ExportedPart]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    writeln!(file, "# Exported from VAIC")?;
+
+    // Indices count up through the whole file, starting at 1
+    let mut next_index = 1;
+    for part in parts {
+        writeln!(file, "o {}", part.name)?;
+        for triangle in &part.triangles {
+            for point in triangle {
+                writeln!(file, "v {} {} {}", point.x, point.y, point.z)?;
+            }
+        }
+        for _ in &part.triangles {
+            writeln!(
+                file,
+                "f {} {} {}",
+                next_index,
+                next_index + 1,
+                next_index + 2
+            )?;
+            next_index += 3;
+        }
+    }
+    file.flush()
+}
@@ -0,0 +1,38 @@
+use std::{
+    fs::File,
+    io::{BufWriter, Write},
+    path::Path,
+};
+
+use bevy::prelude::*;
+
+use super::ExportedPart;
+
+/// Writes a binary STL of every part's triangles together. STL files are usually z up (that's how
+/// slicers lay them on the bed), so the y up coordinates are turned to match
+pub fn write(path: &Path, parts: &[ExportedPart]) -> std::io::Result<()> {
+    let mut file = BufWriter::new(File::create(path)?);
+    let triangle_count: usize = parts.iter().map(|part| part.triangles.len()).sum();
+
+    let mut header = [0u8; 80];
+    let title = b"Exported from VAIC";
+    header[..title.len()].copy_from_slice(title);
+    file.write_all(&header)?;
+    file.write_all(&(triangle_count as u32).to_le_bytes())?;
+
+    let z_up = |point: Vec3| Vec3::new(point.x, -point.z, point.y);
+    for triangle in parts.iter().flat_map(|part| &part.triangles) {
+        let corners = triangle.map(z_up);
+        let normal = (corners[1] - corners[0])
+            .cross(corners[2] - corners[0])
+            .normalize_or_zero();
+        for point in std::iter::once(normal).chain(corners) {
+            for coordinate in point.to_array() {
+                file.write_all(&coordinate.to_le_bytes())?;
+            }
+        }
+        // Attribute byte count, which nothing uses
+        file.write_all(&0u16.to_le_bytes())?;
+    }
+    file.flush()
+}
@@ -0,0 +1,96 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    keybinds::{ActionState, InputAction},
+    move_objects::CurrentlyMoving,
+    units::LengthUnit,
+};
+
+use super::{ExportEvent, ExportFormat, ExportOptions};
+
+pub struct ExportUiPlugin;
+
+impl Plugin for ExportUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ExportWindow>().add_systems(
+            Update,
+            (
+                toggle_export_window,
+                export_window.run_if(|window: Res<ExportWindow>| window.open),
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+struct ExportWindow {
+    open: bool,
+}
+
+fn toggle_export_window(actions: Res<ActionState>, mut window: ResMut<ExportWindow>) {
+    if actions.just_pressed(InputAction::Export) {
+        window.open = !window.open;
+    }
+}
+
+fn export_window(
+    mut contexts: EguiContexts,
+    mut window: ResMut<ExportWindow>,
+    mut options: ResMut<ExportOptions>,
+    mut export_events: EventWriter<ExportEvent>,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+) {
+    let mut open = true;
+    egui::Window::new("Export")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("Format");
+                for format in ExportFormat::ALL {
+                    ui.radio_value(&mut options.format, format, format.label());
+                }
+            });
+            if options.format.is_gltf() {
+                ui.label("glTF files are always in meters");
+            } else {
+                ui.horizontal(|ui| {
+                    ui.label("Units");
+                    // Holes don't mean anything to other programs
+                    for unit in [LengthUnit::Millimeters, LengthUnit::Inches] {
+                        ui.radio_value(&mut options.unit, unit, unit.label());
+                    }
+                });
+            }
+            ui.checkbox(&mut options.separate_files, "A file for each part");
+            ui.horizontal(|ui| {
+                ui.label(if options.separate_files {
+                    "Folder"
+                } else {
+                    "File name"
+                });
+                ui.text_edit_singleline(&mut options.name);
+            });
+            ui.separator();
+
+            let what = if selected_query.is_empty() {
+                "Export everything"
+            } else {
+                "Export selection"
+            };
+            if ui
+                .add_enabled(!options.name.trim().is_empty(), egui::Button::new(what))
+                .clicked()
+            {
+                export_events.send(ExportEvent);
+            }
+            if let Some(status) = &options.status {
+                ui.label(status);
+            }
+        });
+    if !open {
+        window.open = false;
+    }
+}
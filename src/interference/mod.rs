@@ -0,0 +1,324 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    bounds::{world_bounds, world_triangles, BoundsQuery, MeshQuery},
+    move_objects::{gizmo_dragging, CurrentlyMoving},
+    placing::{CurrentlyPlacing, PartName},
+};
+
+mod ui;
+use ui::InterferenceUiPlugin;
+
+pub struct InterferencePlugin;
+
+impl Plugin for InterferencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<CheckInterference>()
+            .init_resource::<Interferences>()
+            .add_plugins(InterferenceUiPlugin)
+            .add_systems(
+                Update,
+                (
+                    check_interference,
+                    check_while_dragging.run_if(
+                        gizmo_dragging
+                            .and_then(|interferences: Res<Interferences>| interferences.live),
+                    ),
+                )
+                    .run_if(in_state(crate::ui::UIState::Editor)),
+            );
+    }
+}
+
+/// How far parts have to sink into each other before it counts, in inches. Parts bolted flat
+/// against each other touch everywhere, and the models aren't exact enough to tell that apart
+/// from a sliver of overlap
+const CONTACT_TOLERANCE: f32 = 0.005;
+
+/// Checks every visible part against every other one
+#[derive(Event, Debug)]
+pub struct CheckInterference;
+
+/// Two parts that occupy the same space
+#[derive(Debug, Clone, Copy)]
+pub struct Interference {
+    pub parts: (Entity, Entity),
+    /// World space box (min, max) around where they overlap
+    pub region: (Vec3, Vec3),
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct Interferences {
+    pub list: Vec<Interference>,
+    /// Whether a full check has been run yet, so "nothing found" can be told apart from "not
+    /// checked"
+    pub checked: bool,
+    /// Re-checks the selection against everything else while it's dragged with the gizmo
+    pub live: bool,
+}
+
+type PartQuery<'w, 's> = Query<
+    'w,
+    's,
+    (Entity, &'static InheritedVisibility),
+    (With<PartName>, Without<CurrentlyPlacing>),
+>;
+
+/// Looks up the shape of each part at most once per check
+struct PartShapes<'a, 'w, 's> {
+    children_query: &'a Query<'w, 's, &'static Children>,
+    aabb_query: &'a BoundsQuery<'w, 's>,
+    mesh_query: &'a MeshQuery<'w, 's>,
+    meshes: &'a Assets<Mesh>,
+    bounds: HashMap<Entity, Option<(Vec3, Vec3)>>,
+    triangles: HashMap<Entity, Vec<[Vec3; 3]>>,
+}
+
+impl<'a, 'w, 's> PartShapes<'a, 'w, 's> {
+    fn bounds(&mut self, part: Entity) -> Option<(Vec3, Vec3)> {
+        *self
+            .bounds
+            .entry(part)
+            .or_insert_with(|| world_bounds(part, self.children_query, self.aabb_query))
+    }
+
+    fn triangles(&mut self, part: Entity) -> &[[Vec3; 3]] {
+        self.triangles.entry(part).or_insert_with(|| {
+            world_triangles(part, self.children_query, self.mesh_query, self.meshes)
+        })
+    }
+
+    /// Broad phase on the bounding boxes, then the triangles near the overlap against each other
+    fn interference(&mut self, a: Entity, b: Entity) -> Option<Interference> {
+        let overlap = box_overlap(self.bounds(a)?, self.bounds(b)?)?;
+        let near = |triangles: &[[Vec3; 3]]| -> Vec<([Vec3; 3], (Vec3, Vec3))> {
+            triangles
+                .iter()
+                .map(|triangle| (*triangle, triangle_bounds(triangle)))
+                .filter(|(_, bounds)| box_overlap(*bounds, overlap).is_some())
+                .collect()
+        };
+        let near_a = near(self.triangles(a));
+        let near_b = near(self.triangles(b));
+
+        let mut region: Option<(Vec3, Vec3)> = None;
+        for (triangle_a, bounds_a) in &near_a {
+            for (triangle_b, bounds_b) in &near_b {
+                if box_overlap(*bounds_a, *bounds_b).is_none()
+                    || !triangles_penetrate(triangle_a, triangle_b)
+                {
+                    continue;
+                }
+                let Some((min, max)) = box_overlap(union(*bounds_a, *bounds_b), overlap) else {
+                    continue;
+                };
+                region = Some(match region {
+                    Some(region) => union(region, (min, max)),
+                    None => (min, max),
+                });
+            }
+        }
+        region.map(|region| Interference {
+            parts: (a, b),
+            region,
+        })
+    }
+}
+
+fn check_interference(
+    mut check_events: EventReader<CheckInterference>,
+    mut interferences: ResMut<Interferences>,
+    part_query: PartQuery,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+    mesh_query: MeshQuery,
+    meshes: Res<Assets<Mesh>>,
+) {
+    if check_events.read().count() == 0 {
+        return;
+    }
+    let parts: Vec<Entity> = part_query
+        .iter()
+        .filter(|(_, visibility)| visibility.get())
+        .map(|(entity, _)| entity)
+        .collect();
+    let mut shapes = PartShapes {
+        children_query: &children_query,
+        aabb_query: &aabb_query,
+        mesh_query: &mesh_query,
+        meshes: &meshes,
+        bounds: HashMap::new(),
+        triangles: HashMap::new(),
+    };
+
+    let mut list = Vec::new();
+    for (index, a) in parts.iter().enumerate() {
+        for b in &parts[index + 1..] {
+            list.extend(shapes.interference(*a, *b));
+        }
+    }
+    interferences.list = list;
+    interferences.checked = true;
+}
+
+/// Only pairs with a part that's being moved can change during a drag, so those are the only
+/// ones checked again
+fn check_while_dragging(
+    mut interferences: ResMut<Interferences>,
+    part_query: PartQuery,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+    mesh_query: MeshQuery,
+    meshes: Res<Assets<Mesh>>,
+) {
+    // Parts inside a selected assembly move with it
+    let is_moving = |part: Entity| {
+        selected_query.contains(part)
+            || parent_query
+                .iter_ancestors(part)
+                .any(|ancestor| selected_query.contains(ancestor))
+    };
+    let (moving, still): (Vec<Entity>, Vec<Entity>) = part_query
+        .iter()
+        .filter(|(_, visibility)| visibility.get())
+        .map(|(entity, _)| entity)
+        .partition(|part| is_moving(*part));
+    let mut shapes = PartShapes {
+        children_query: &children_query,
+        aabb_query: &aabb_query,
+        mesh_query: &mesh_query,
+        meshes: &meshes,
+        bounds: HashMap::new(),
+        triangles: HashMap::new(),
+    };
+
+    interferences.list.retain(|interference| {
+        !is_moving(interference.parts.0) && !is_moving(interference.parts.1)
+    });
+    for a in &moving {
+        for b in &still {
+            if let Some(interference) = shapes.interference(*a, *b) {
+                interferences.list.push(interference);
+            }
+        }
+    }
+}
+
+/// The box where two boxes overlap, if they do
+fn box_overlap(a: (Vec3, Vec3), b: (Vec3, Vec3)) -> Option<(Vec3, Vec3)> {
+    let min = a.0.max(b.0);
+    let max = a.1.min(b.1);
+    min.cmple(max).all().then_some((min, max))
+}
+
+fn union(a: (Vec3, Vec3), b: (Vec3, Vec3)) -> (Vec3, Vec3) {
+    (a.0.min(b.0), a.1.max(b.1))
+}
+
+fn triangle_bounds(triangle: &[Vec3; 3]) -> (Vec3, Vec3) {
+    (
+        triangle[0].min(triangle[1]).min(triangle[2]),
+        triangle[0].max(triangle[1]).max(triangle[2]),
+    )
+}
+
+/// Triangle intersection test after Möller. Faces that only touch (including coplanar ones)
+/// don't count: each triangle has to reach more than [`CONTACT_TOLERANCE`] through the other's
+/// plane on both sides, and where they cross the line the planes meet on, the two have to
+/// overlap
+fn triangles_penetrate(a: &[Vec3; 3], b: &[Vec3; 3]) -> bool {
+    let (Some(normal_a), Some(normal_b)) = (
+        (a[1] - a[0]).cross(a[2] - a[0]).try_normalize(),
+        (b[1] - b[0]).cross(b[2] - b[0]).try_normalize(),
+    ) else {
+        // Degenerate triangles have no inside to sink into
+        return false;
+    };
+    // How far each corner is from the other triangle's plane
+    let distances_a = a.map(|point| normal_b.dot(point - b[0]));
+    let distances_b = b.map(|point| normal_a.dot(point - a[0]));
+    let crosses = |distances: [f32; 3]| {
+        let below = distances[0].min(distances[1]).min(distances[2]);
+        let above = distances[0].max(distances[1]).max(distances[2]);
+        below < -CONTACT_TOLERANCE && above > CONTACT_TOLERANCE
+    };
+    if !crosses(distances_a) || !crosses(distances_b) {
+        return false;
+    }
+
+    // Both triangles cross the line where the planes meet, each along an interval of it
+    let direction = normal_a.cross(normal_b);
+    let interval = |triangle: &[Vec3; 3], distances: [f32; 3]| {
+        let mut min = f32::INFINITY;
+        let mut max = f32::NEG_INFINITY;
+        for (start, end) in [(0, 1), (1, 2), (2, 0)] {
+            let (from, to) = (distances[start], distances[end]);
+            if from * to > 0.0 || from == to {
+                continue;
+            }
+            let point = triangle[start].lerp(triangle[end], from / (from - to));
+            let along = direction.dot(point);
+            min = min.min(along);
+            max = max.max(along);
+        }
+        (min, max)
+    };
+    let (min_a, max_a) = interval(a, distances_a);
+    let (min_b, max_b) = interval(b, distances_b);
+    max_a.min(max_b) > min_a.max(min_b)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle(points: [[f32; 3]; 3]) -> [Vec3; 3] {
+        points.map(Vec3::from_array)
+    }
+
+    #[test]
+    fn crossing_triangles_penetrate() {
+        let flat = triangle([[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 0.0, 1.0]]);
+        let upright = triangle([[0.0, -1.0, 0.0], [0.0, 1.0, -0.5], [0.0, 1.0, 0.5]]);
+        assert!(triangles_penetrate(&flat, &upright));
+        assert!(triangles_penetrate(&upright, &flat));
+    }
+
+    #[test]
+    fn sunk_in_triangles_penetrate() {
+        // Edges that line up with each other, like on most of the parts
+        let flat = triangle([[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [-1.0, 0.0, 1.0]]);
+        let sunk = triangle([[-0.5, -0.1, 0.0], [0.5, -0.1, 0.0], [-0.5, 1.0, 0.0]]);
+        assert!(triangles_penetrate(&flat, &sunk));
+    }
+
+    #[test]
+    fn separate_triangles_dont_penetrate() {
+        let flat = triangle([[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 0.0, 1.0]]);
+        let above = triangle([[0.0, 0.5, 0.0], [0.0, 1.5, -0.5], [0.0, 1.5, 0.5]]);
+        assert!(!triangles_penetrate(&flat, &above));
+        // Crosses the plane, but next to the triangle
+        let beside = triangle([[3.0, -1.0, 0.0], [3.0, 1.0, -0.5], [3.0, 1.0, 0.5]]);
+        assert!(!triangles_penetrate(&flat, &beside));
+    }
+
+    #[test]
+    fn touching_triangles_dont_penetrate() {
+        let flat = triangle([[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 0.0, 1.0]]);
+        // Stands on the flat one with an edge along its face
+        let standing = triangle([[-0.5, 0.0, 0.0], [0.5, 0.0, 0.0], [0.0, 1.0, 0.0]]);
+        assert!(!triangles_penetrate(&flat, &standing));
+        // Sunk in by less than the tolerance
+        let barely = triangle([[-0.5, -0.001, 0.0], [0.5, -0.001, 0.0], [0.0, 1.0, 0.0]]);
+        assert!(!triangles_penetrate(&flat, &barely));
+    }
+
+    #[test]
+    fn coplanar_triangles_dont_penetrate() {
+        let a = triangle([[-1.0, 0.0, -1.0], [1.0, 0.0, -1.0], [0.0, 0.0, 1.0]]);
+        let b = triangle([[-1.0, 0.0, 1.0], [1.0, 0.0, 1.0], [0.0, 0.0, -1.0]]);
+        assert!(!triangles_penetrate(&a, &b));
+    }
+}
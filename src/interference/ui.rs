@@ -0,0 +1,151 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    assembly::Assembly,
+    keybinds::{ActionState, InputAction},
+    move_objects::{select_parts, CurrentlyMoving, MoveObjectsState},
+    placing::PartName,
+};
+
+use super::{CheckInterference, Interferences};
+
+pub struct InterferenceUiPlugin;
+
+impl Plugin for InterferenceUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InterferenceWindow>().add_systems(
+            Update,
+            (
+                toggle_interference_window,
+                interference_window,
+                draw_interference,
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+const INTERFERENCE_COLOR: Color = Color::rgb(1.0, 0.15, 0.1);
+
+#[derive(Resource, Default, Debug)]
+struct InterferenceWindow {
+    open: bool,
+}
+
+/// Opening the window runs a check right away
+fn toggle_interference_window(
+    actions: Res<ActionState>,
+    mut window: ResMut<InterferenceWindow>,
+    mut check_events: EventWriter<CheckInterference>,
+) {
+    if actions.just_pressed(InputAction::Interference) {
+        window.open = !window.open;
+        if window.open {
+            check_events.send(CheckInterference);
+        }
+    }
+}
+
+fn interference_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut window: ResMut<InterferenceWindow>,
+    mut interferences: ResMut<Interferences>,
+    mut check_events: EventWriter<CheckInterference>,
+    name_query: Query<(&PartName, Option<&Name>)>,
+    parent_query: Query<&Parent>,
+    assembly_query: Query<(), With<Assembly>>,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    mut moving_state: ResMut<NextState<MoveObjectsState>>,
+) {
+    if !window.open {
+        return;
+    }
+    let label = |part: Entity| {
+        name_query
+            .get(part)
+            .map(|(part_name, name)| match name {
+                Some(name) => name.to_string(),
+                // Imported parts aren't in the catalog, so they go by their path
+                None => crate::catalog::find_by_scene_path(&part_name.0)
+                    .map_or(part_name.0.clone(), |entry| entry.name.to_string()),
+            })
+            .ok()
+    };
+
+    let mut open = true;
+    let mut selected = None;
+    egui::Window::new("Interference")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                if ui.button("Check all parts").clicked() {
+                    check_events.send(CheckInterference);
+                }
+                ui.checkbox(&mut interferences.live, "Check while dragging");
+            });
+            ui.separator();
+
+            // Parts deleted since the last check drop out of the list
+            let pairs: Vec<_> = interferences
+                .list
+                .iter()
+                .filter_map(|interference| {
+                    let (a, b) = interference.parts;
+                    Some((interference.parts, label(a)?, label(b)?))
+                })
+                .collect();
+            if pairs.is_empty() {
+                ui.label(if interferences.checked {
+                    "No parts overlap"
+                } else {
+                    "Not checked yet"
+                });
+            }
+            for (parts, label_a, label_b) in pairs {
+                if ui
+                    .selectable_label(false, format!("{label_a}  ×  {label_b}"))
+                    .on_hover_text("Select both parts")
+                    .clicked()
+                {
+                    selected = Some(parts);
+                }
+            }
+        });
+    if !open {
+        window.open = false;
+    }
+
+    if let Some((a, b)) = selected {
+        select_parts(
+            &mut commands,
+            [a, b],
+            &selected_query,
+            &parent_query,
+            &assembly_query,
+            &mut moving_state,
+        );
+    }
+}
+
+/// Outlines where parts overlap, whether or not the window is open
+fn draw_interference(
+    mut gizmos: Gizmos,
+    interferences: Res<Interferences>,
+    part_query: Query<(), With<PartName>>,
+) {
+    for interference in &interferences.list {
+        let (a, b) = interference.parts;
+        if !part_query.contains(a) || !part_query.contains(b) {
+            continue;
+        }
+        let (min, max) = interference.region;
+        gizmos.cuboid(
+            Transform::from_translation((min + max) / 2.0)
+                .with_scale((max - min).max(Vec3::splat(0.01))),
+            INTERFERENCE_COLOR,
+        );
+    }
+}
@@ -0,0 +1,262 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::bom::{stock_key, BomLine};
+
+mod ui;
+use ui::InventoryUiPlugin;
+
+pub struct InventoryPlugin;
+
+impl Plugin for InventoryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Inventory>()
+            .add_plugins(InventoryUiPlugin);
+    }
+}
+
+/// The team's stock of parts, to check the robot can actually be built
+#[derive(Resource, Debug)]
+pub struct Inventory {
+    /// CSV file the stock is read from
+    pub path: String,
+    /// How many of each part the team has, by [`stock_key`]. `None` until a file is loaded
+    pub stock: Option<HashMap<String, usize>>,
+    pub error: Option<String>,
+    /// Outlines parts the team doesn't have enough of
+    pub show_shortages: bool,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        Self {
+            path: String::from("inventory.csv"),
+            stock: None,
+            error: None,
+            show_shortages: true,
+        }
+    }
+}
+
+impl Inventory {
+    pub fn load(&mut self) {
+        match std::fs::read_to_string(&self.path)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| parse_inventory(&contents))
+        {
+            Ok(stock) => {
+                self.stock = Some(stock);
+                self.error = None;
+            }
+            Err(error) => self.error = Some(format!("Couldn't read {}: {error}", self.path)),
+        }
+    }
+}
+
+/// How a kind of part in the robot or the inventory compares to what's owned
+#[derive(Debug, Clone, PartialEq)]
+pub struct StockRow {
+    /// Catalog name, or the stock key for parts that are only in the inventory
+    pub name: String,
+    pub needed: usize,
+    pub owned: usize,
+    /// Whether the robot uses the part at all
+    pub used: bool,
+}
+
+impl StockRow {
+    pub fn short(&self) -> usize {
+        self.needed.saturating_sub(self.owned)
+    }
+
+    pub fn spare(&self) -> usize {
+        self.owned.saturating_sub(self.needed)
+    }
+}
+
+/// One row per line of the bill of materials, then whatever else is in stock sorted by key
+pub fn compare_stock(lines: &[BomLine], stock: &HashMap<String, usize>) -> Vec<StockRow> {
+    let mut rows: Vec<_> = lines
+        .iter()
+        .map(|line| StockRow {
+            name: line.name.clone(),
+            needed: line.quantity,
+            owned: line
+                .stock_keys()
+                .iter()
+                .filter_map(|key| stock.get(key))
+                .sum(),
+            used: true,
+        })
+        .collect();
+    let used: Vec<_> = lines.iter().flat_map(BomLine::stock_keys).collect();
+    let mut unused: Vec<_> = stock
+        .iter()
+        .filter(|(key, owned)| **owned > 0 && !used.contains(*key))
+        .collect();
+    unused.sort();
+    rows.extend(unused.into_iter().map(|(key, owned)| StockRow {
+        name: key.clone(),
+        needed: 0,
+        owned: *owned,
+        used: false,
+    }));
+    rows
+}
+
+/// Splits a CSV line into its fields, handling quoted fields with commas and `""` in them
+fn split_csv_row(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut quoted = false;
+    let mut characters = line.chars().peekable();
+    while let Some(character) = characters.next() {
+        match character {
+            '"' if quoted && characters.peek() == Some(&'"') => {
+                characters.next();
+                fields.last_mut().unwrap().push('"');
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => fields.push(String::new()),
+            _ => fields.last_mut().unwrap().push(character),
+        }
+    }
+    fields
+}
+
+/// Reads part number and quantity columns. Without a header the first two columns are used, with
+/// one the columns can be anywhere (so an exported bill of materials works as an inventory too)
+fn parse_inventory(text: &str) -> Result<HashMap<String, usize>, String> {
+    let mut rows = text
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(index, line)| (index + 1, split_csv_row(line)))
+        .peekable();
+
+    let (mut part_column, mut name_column, mut quantity_column) = (Some(0), None, 1);
+    let mut has_header = false;
+    if let Some((_, header)) = rows.peek() {
+        let find = |names: &[&str]| {
+            header
+                .iter()
+                .position(|field| names.contains(&field.trim().to_lowercase().as_str()))
+        };
+        if let Some(column) = find(&["quantity", "qty", "count"]) {
+            quantity_column = column;
+            part_column = find(&["part number", "part_number", "part", "sku"]);
+            name_column = find(&["name"]);
+            has_header = true;
+        }
+    }
+    if has_header {
+        rows.next();
+    }
+
+    let mut stock = HashMap::new();
+    for (line_number, row) in rows {
+        let field = |column: Option<usize>| {
+            column
+                .and_then(|column| row.get(column))
+                .map(|field| field.trim())
+                .filter(|field| !field.is_empty())
+        };
+        let Some(name) = field(part_column).or(field(name_column)) else {
+            return Err(format!("line {line_number} has no part number"));
+        };
+        let quantity = field(Some(quantity_column))
+            .and_then(|quantity| quantity.parse::<usize>().ok())
+            .ok_or_else(|| format!("line {line_number} has no quantity"))?;
+        *stock.entry(stock_key(name)).or_insert(0) += quantity;
+    }
+    Ok(stock)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{bom::bill_of_materials, catalog::PartCategory};
+
+    #[test]
+    fn splits_quoted_fields() {
+        assert_eq!(split_csv_row("a,b,,c"), ["a", "b", "", "c"]);
+        assert_eq!(
+            split_csv_row("\"Bolt, 8-32\",\"say \"\"hi\"\"\",3"),
+            ["Bolt, 8-32", "say \"hi\"", "3"]
+        );
+    }
+
+    #[test]
+    fn reads_the_first_two_columns_without_a_header() {
+        let stock = parse_inventory("276-1234, 4\n\n2x1 C-Channel,2\n276-1234,1\n").unwrap();
+        assert_eq!(stock.len(), 2);
+        assert_eq!(stock["276-1234"], 5);
+        assert_eq!(stock["2x1 c-channel"], 2);
+    }
+
+    #[test]
+    fn finds_columns_by_header() {
+        let text = "Category,Part number,Name,Quantity,Unit cost\n\
+                    Structure,,Cube,3,\n\
+                    Motion,276-9999,Gear,2,1.00\n";
+        let stock = parse_inventory(text).unwrap();
+        assert_eq!(stock["cube"], 3);
+        assert_eq!(stock["276-9999"], 2);
+    }
+
+    #[test]
+    fn reports_bad_lines() {
+        assert_eq!(
+            parse_inventory("Cube,3\n,2\n").unwrap_err(),
+            "line 2 has no part number"
+        );
+        assert_eq!(
+            parse_inventory("Cube,lots\n").unwrap_err(),
+            "line 1 has no quantity"
+        );
+    }
+
+    #[test]
+    fn compares_needed_and_owned() {
+        let line = |name: &str, quantity| BomLine {
+            category: PartCategory::Other,
+            id: name.to_lowercase(),
+            name: name.to_string(),
+            part_number: None,
+            quantity,
+            unit_cost: None,
+        };
+        let lines = [line("Cube", 3), line("Duck", 1), line("Penguin", 2)];
+        let stock =
+            parse_inventory("cube,1\nDuck,4\npenguin,2\nzip tie,0\ngear,5\nbolt,7").unwrap();
+        let rows = compare_stock(&lines, &stock);
+
+        let summary: Vec<_> = rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.short(), row.spare(), row.used))
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                ("Cube", 2, 0, true),
+                ("Duck", 0, 3, true),
+                ("Penguin", 0, 0, true),
+                ("bolt", 0, 7, false),
+                ("gear", 0, 5, false),
+            ]
+        );
+    }
+
+    #[test]
+    fn matches_parts_by_part_number_or_catalog_id() {
+        let mut lines = bill_of_materials([
+            "models/c-channel-1x2x1x1.glb#Scene0",
+            "models/c-channel-1x2x1x1.glb#Scene0",
+            "models/c-channel-1x2x1x25.glb#Scene0",
+        ]);
+        lines[1].part_number = Some("276-1234".to_string());
+        let stock = parse_inventory("Part number,Qty\nc-channel-1x2x1x1,1\n276-1234,3\n").unwrap();
+        let rows = compare_stock(&lines, &stock);
+        assert_eq!(rows.len(), 2);
+        assert_eq!((rows[0].needed, rows[0].owned, rows[0].short()), (2, 1, 1));
+        assert_eq!((rows[1].needed, rows[1].owned, rows[1].spare()), (1, 3, 2));
+    }
+}
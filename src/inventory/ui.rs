@@ -0,0 +1,163 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    bom::{bill_of_materials, part_id},
+    bounds::{world_bounds, BoundsQuery},
+    keybinds::{ActionState, InputAction},
+    placing::{CurrentlyPlacing, PartName},
+};
+
+use super::{compare_stock, Inventory};
+
+pub struct InventoryUiPlugin;
+
+impl Plugin for InventoryUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<InventoryWindow>().add_systems(
+            Update,
+            (
+                toggle_inventory_window,
+                inventory_window.run_if(|window: Res<InventoryWindow>| window.open),
+                draw_shortages.run_if(|inventory: Res<Inventory>| {
+                    inventory.show_shortages && inventory.stock.is_some()
+                }),
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+const SHORTAGE_COLOR: Color = Color::rgb(1.0, 0.45, 0.0);
+
+#[derive(Resource, Default, Debug)]
+struct InventoryWindow {
+    open: bool,
+}
+
+fn toggle_inventory_window(actions: Res<ActionState>, mut window: ResMut<InventoryWindow>) {
+    if actions.just_pressed(InputAction::Inventory) {
+        window.open = !window.open;
+    }
+}
+
+fn inventory_window(
+    mut contexts: EguiContexts,
+    mut window: ResMut<InventoryWindow>,
+    mut inventory: ResMut<Inventory>,
+    part_query: Query<&PartName, Without<CurrentlyPlacing>>,
+) {
+    let lines = bill_of_materials(part_query.iter().map(|part_name| part_name.0.as_str()));
+
+    let mut open = true;
+    egui::Window::new("Inventory")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.label("CSV file");
+                ui.text_edit_singleline(&mut inventory.path);
+                if ui.button("Load").clicked() {
+                    inventory.load();
+                }
+            });
+            ui.label("One part per line: part number (or catalog ID or name), quantity");
+            if let Some(error) = &inventory.error {
+                ui.colored_label(egui::Color32::RED, error);
+            }
+            let Some(stock) = &inventory.stock else {
+                return;
+            };
+            ui.separator();
+
+            let rows = compare_stock(&lines, stock);
+            let short = rows.iter().filter(|row| row.short() > 0).count();
+            egui::ScrollArea::vertical()
+                .max_height(300.0)
+                .show(ui, |ui| {
+                    egui::Grid::new("Inventory comparison")
+                        .num_columns(4)
+                        .striped(true)
+                        .show(ui, |ui| {
+                            ui.strong("Part");
+                            ui.strong("Needed");
+                            ui.strong("Owned");
+                            ui.strong("");
+                            ui.end_row();
+
+                            for row in &rows {
+                                if row.used {
+                                    ui.label(&row.name);
+                                } else {
+                                    ui.weak(&row.name);
+                                }
+                                ui.label(row.needed.to_string());
+                                ui.label(row.owned.to_string());
+                                if row.short() > 0 {
+                                    ui.colored_label(
+                                        egui::Color32::RED,
+                                        format!("{} short", row.short()),
+                                    );
+                                } else if row.spare() > 0 {
+                                    ui.label(format!("{} spare", row.spare()));
+                                } else {
+                                    ui.label("");
+                                }
+                                ui.end_row();
+                            }
+                        });
+                });
+            ui.separator();
+            if short == 0 {
+                ui.colored_label(egui::Color32::GREEN, "Everything is in stock");
+            } else {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!("Not enough of {short} kinds of part"),
+                );
+            }
+            ui.checkbox(
+                &mut inventory.show_shortages,
+                "Outline parts we don't have enough of",
+            );
+        });
+    if !open {
+        window.open = false;
+    }
+}
+
+/// Outlines every placed part of a kind the team has too few of
+fn draw_shortages(
+    mut gizmos: Gizmos,
+    inventory: Res<Inventory>,
+    part_query: Query<(Entity, &PartName, &InheritedVisibility), Without<CurrentlyPlacing>>,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+) {
+    let Some(stock) = &inventory.stock else {
+        return;
+    };
+    let lines = bill_of_materials(
+        part_query
+            .iter()
+            .map(|(_, part_name, _)| part_name.0.as_str()),
+    );
+    // The rows start with one for each line, in the same order
+    let short: Vec<&str> = lines
+        .iter()
+        .zip(compare_stock(&lines, stock))
+        .filter(|(_, row)| row.short() > 0)
+        .map(|(line, _)| line.id.as_str())
+        .collect();
+    for (entity, part_name, visibility) in part_query.iter() {
+        if !visibility.get() || !short.contains(&part_id(&part_name.0)) {
+            continue;
+        }
+        if let Some((min, max)) = world_bounds(entity, &children_query, &aabb_query) {
+            gizmos.cuboid(
+                Transform::from_translation((min + max) / 2.0).with_scale(max - min),
+                SHORTAGE_COLOR,
+            );
+        }
+    }
+}
@@ -0,0 +1,695 @@
+use std::collections::{BTreeMap, HashSet};
+
+use bevy::prelude::*;
+use bevy_egui::{EguiContexts, EguiSet};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{move_objects::NumericEntry, settings::Settings};
+
+pub struct KeybindsPlugin;
+
+impl Plugin for KeybindsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ActionState>()
+            .init_resource::<Rebinding>()
+            .add_systems(PreUpdate, update_action_state.after(EguiSet::BeginFrame));
+    }
+}
+
+/// Everything that can be done with a keyboard shortcut
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum InputAction {
+    Undo,
+    Save,
+    Delete,
+    /// Leaves whatever is going on (placing, constraining, the selection), or pauses
+    Cancel,
+    Constrain,
+    ToggleGizmoMode,
+    ToggleGizmoOrientation,
+    CyclePivot,
+    CycleSnapMode,
+    /// Held while dragging
+    DisableSnapping,
+    /// Focuses the box for typing an exact value after a gizmo drag
+    TypeValue,
+    Pattern,
+    Mirror,
+    Group,
+    Ungroup,
+    InsertAssembly,
+    ToggleGrid,
+    ToggleProjection,
+    ToggleControlMode,
+    Screenshot,
+    ViewFront,
+    ViewBack,
+    ViewLeft,
+    ViewRight,
+    ViewTop,
+    ViewBottom,
+    ViewIsometric,
+    FrameAll,
+    FrameSelection,
+    ToggleViews,
+    Measure,
+    ToggleMeasurements,
+    Interference,
+    Legality,
+    MassProperties,
+    Inventory,
+    Export,
+}
+
+impl InputAction {
+    pub const ALL: [InputAction; 37] = [
+        Self::Undo,
+        Self::Save,
+        Self::Delete,
+        Self::Cancel,
+        Self::Constrain,
+        Self::ToggleGizmoMode,
+        Self::ToggleGizmoOrientation,
+        Self::CyclePivot,
+        Self::CycleSnapMode,
+        Self::DisableSnapping,
+        Self::TypeValue,
+        Self::Pattern,
+        Self::Mirror,
+        Self::Group,
+        Self::Ungroup,
+        Self::InsertAssembly,
+        Self::ToggleGrid,
+        Self::ToggleProjection,
+        Self::ToggleControlMode,
+        Self::Screenshot,
+        Self::ViewFront,
+        Self::ViewBack,
+        Self::ViewLeft,
+        Self::ViewRight,
+        Self::ViewTop,
+        Self::ViewBottom,
+        Self::ViewIsometric,
+        Self::FrameAll,
+        Self::FrameSelection,
+        Self::ToggleViews,
+        Self::Measure,
+        Self::ToggleMeasurements,
+        Self::Interference,
+        Self::Legality,
+        Self::MassProperties,
+        Self::Inventory,
+        Self::Export,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Undo => "Undo",
+            Self::Save => "Save",
+            Self::Delete => "Delete selection",
+            Self::Cancel => "Cancel / pause",
+            Self::Constrain => "Constrain",
+            Self::ToggleGizmoMode => "Move / rotate gizmo",
+            Self::ToggleGizmoOrientation => "Global / local gizmo",
+            Self::CyclePivot => "Change pivot",
+            Self::CycleSnapMode => "Change snapping",
+            Self::DisableSnapping => "Hold to stop snapping",
+            Self::TypeValue => "Type an exact value",
+            Self::Pattern => "Pattern",
+            Self::Mirror => "Mirror",
+            Self::Group => "Group into assembly",
+            Self::Ungroup => "Ungroup assembly",
+            Self::InsertAssembly => "Insert assembly",
+            Self::ToggleGrid => "Show / hide grid",
+            Self::ToggleProjection => "Switch projection",
+            Self::ToggleControlMode => "Switch control mode",
+            Self::Screenshot => "Screenshot",
+            Self::ViewFront => "Front view",
+            Self::ViewBack => "Back view",
+            Self::ViewLeft => "Left view",
+            Self::ViewRight => "Right view",
+            Self::ViewTop => "Top view",
+            Self::ViewBottom => "Bottom view",
+            Self::ViewIsometric => "Isometric view",
+            Self::FrameAll => "Fit everything in view",
+            Self::FrameSelection => "Fit selection in view",
+            Self::ToggleViews => "Saved views",
+            Self::Measure => "Measure",
+            Self::ToggleMeasurements => "Show / hide measurements",
+            Self::Interference => "Interference check",
+            Self::Legality => "Robot rules check",
+            Self::MassProperties => "Mass and center of gravity",
+            Self::Inventory => "Compare with inventory",
+            Self::Export => "Export model",
+        }
+    }
+
+    fn default_chords(&self) -> Vec<KeyChord> {
+        let key = KeyChord::key;
+        match self {
+            Self::Undo => vec![key(KeyCode::KeyZ).ctrl()],
+            Self::Save => vec![key(KeyCode::KeyS).ctrl()],
+            Self::Delete => vec![key(KeyCode::KeyX), key(KeyCode::Delete)],
+            Self::Cancel => vec![key(KeyCode::Escape)],
+            Self::Constrain => vec![key(KeyCode::KeyC)],
+            Self::ToggleGizmoMode => vec![key(KeyCode::KeyT)],
+            Self::ToggleGizmoOrientation => vec![key(KeyCode::Comma)],
+            Self::CyclePivot => vec![key(KeyCode::Period)],
+            Self::CycleSnapMode => vec![key(KeyCode::KeyG)],
+            Self::DisableSnapping => vec![key(KeyCode::ShiftLeft)],
+            Self::TypeValue => vec![key(KeyCode::Tab)],
+            Self::Pattern => vec![key(KeyCode::KeyN)],
+            Self::Mirror => vec![key(KeyCode::KeyM)],
+            Self::Group => vec![key(KeyCode::KeyG).ctrl()],
+            Self::Ungroup => vec![key(KeyCode::KeyG).ctrl().shift()],
+            Self::InsertAssembly => vec![key(KeyCode::KeyI)],
+            Self::ToggleGrid => vec![key(KeyCode::Equal)],
+            Self::ToggleProjection => vec![key(KeyCode::KeyO)],
+            Self::ToggleControlMode => vec![key(KeyCode::KeyP)],
+            Self::Screenshot => vec![key(KeyCode::F12)],
+            Self::ViewFront => vec![key(KeyCode::Numpad1), key(KeyCode::Digit1)],
+            Self::ViewBack => vec![key(KeyCode::Numpad1).ctrl(), key(KeyCode::Digit1).ctrl()],
+            Self::ViewRight => vec![key(KeyCode::Numpad3), key(KeyCode::Digit3)],
+            Self::ViewLeft => vec![key(KeyCode::Numpad3).ctrl(), key(KeyCode::Digit3).ctrl()],
+            Self::ViewTop => vec![key(KeyCode::Numpad7), key(KeyCode::Digit7)],
+            Self::ViewBottom => vec![key(KeyCode::Numpad7).ctrl(), key(KeyCode::Digit7).ctrl()],
+            Self::ViewIsometric => vec![key(KeyCode::Numpad0), key(KeyCode::Digit0)],
+            Self::FrameAll => vec![key(KeyCode::Home)],
+            Self::FrameSelection => vec![key(KeyCode::KeyF)],
+            Self::ToggleViews => vec![key(KeyCode::KeyV)],
+            Self::Measure => vec![key(KeyCode::KeyR)],
+            Self::ToggleMeasurements => vec![key(KeyCode::KeyR).shift()],
+            Self::Interference => vec![key(KeyCode::KeyK)],
+            Self::Legality => vec![key(KeyCode::KeyL)],
+            Self::MassProperties => vec![key(KeyCode::KeyB)],
+            Self::Inventory => vec![key(KeyCode::KeyJ)],
+            Self::Export => vec![key(KeyCode::KeyE)],
+        }
+    }
+}
+
+/// Modifier keys, either side counts and Cmd counts as Ctrl
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Modifiers {
+    pub ctrl: bool,
+    pub shift: bool,
+    pub alt: bool,
+}
+
+impl Modifiers {
+    pub fn pressed(keyboard: &ButtonInput<KeyCode>) -> Self {
+        Self {
+            ctrl: keyboard.any_pressed([
+                KeyCode::ControlLeft,
+                KeyCode::ControlRight,
+                KeyCode::SuperLeft,
+                KeyCode::SuperRight,
+            ]),
+            shift: keyboard.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight]),
+            alt: keyboard.any_pressed([KeyCode::AltLeft, KeyCode::AltRight]),
+        }
+    }
+}
+
+fn is_modifier(key: KeyCode) -> bool {
+    matches!(
+        key,
+        KeyCode::ControlLeft
+            | KeyCode::ControlRight
+            | KeyCode::SuperLeft
+            | KeyCode::SuperRight
+            | KeyCode::ShiftLeft
+            | KeyCode::ShiftRight
+            | KeyCode::AltLeft
+            | KeyCode::AltRight
+    )
+}
+
+/// A key along with the modifiers that have to be held with it, saved as text like "Ctrl+Shift+G"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct KeyChord {
+    pub key: KeyCode,
+    pub modifiers: Modifiers,
+}
+
+impl KeyChord {
+    pub fn key(key: KeyCode) -> Self {
+        Self {
+            key,
+            modifiers: Modifiers::default(),
+        }
+    }
+
+    fn ctrl(mut self) -> Self {
+        self.modifiers.ctrl = true;
+        self
+    }
+
+    fn shift(mut self) -> Self {
+        self.modifiers.shift = true;
+        self
+    }
+
+    /// Modifiers have to match exactly, so G and Ctrl-G don't both go off. A modifier key on its
+    /// own is always allowed to be held with other modifiers
+    fn matches(&self, modifiers: Modifiers) -> bool {
+        is_modifier(self.key) || self.modifiers == modifiers
+    }
+}
+
+impl std::fmt::Display for KeyChord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.modifiers.ctrl {
+            write!(f, "Ctrl+")?;
+        }
+        if self.modifiers.shift {
+            write!(f, "Shift+")?;
+        }
+        if self.modifiers.alt {
+            write!(f, "Alt+")?;
+        }
+        write!(f, "{}", key_name(self.key).unwrap_or("?"))
+    }
+}
+
+impl std::str::FromStr for KeyChord {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = Modifiers::default();
+        let mut parts: Vec<&str> = text.split('+').map(|part| part.trim()).collect();
+        let key = parts.pop().unwrap_or_default();
+        for modifier in parts {
+            match modifier {
+                "Ctrl" => modifiers.ctrl = true,
+                "Shift" => modifiers.shift = true,
+                "Alt" => modifiers.alt = true,
+                _ => return Err(format!("Unknown modifier {modifier} in {text}")),
+            }
+        }
+        let key = KEY_NAMES
+            .iter()
+            .find(|(_, name)| *name == key)
+            .map(|(key, _)| *key)
+            .ok_or_else(|| format!("Unknown key {key} in {text}"))?;
+        Ok(Self { key, modifiers })
+    }
+}
+
+impl Serialize for KeyChord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for KeyChord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        String::deserialize(deserializer)?
+            .parse()
+            .map_err(serde::de::Error::custom)
+    }
+}
+
+/// Which chords do each action
+#[derive(Serialize, Clone, Debug, PartialEq)]
+pub struct Keybinds(pub BTreeMap<InputAction, Vec<KeyChord>>);
+
+/// Settings written by a newer version can have actions and keys this version doesn't know, which
+/// are left out instead of making the whole settings file unreadable. An action that had only
+/// unknown chords is left out too, so [`Keybinds::fill_missing`] gives it its default ones
+impl<'de> Deserialize<'de> for Keybinds {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let WrittenKeybinds(written) = WrittenKeybinds::deserialize(deserializer)?;
+        let keybinds = written
+            .into_iter()
+            .filter_map(|(ActionName(action), written_chords)| {
+                let chords: Vec<KeyChord> = written_chords
+                    .iter()
+                    .filter_map(|chord| chord.parse().ok())
+                    .collect();
+                if chords.is_empty() && !written_chords.is_empty() {
+                    return None;
+                }
+                Some((action?, chords))
+            })
+            .collect();
+        Ok(Self(keybinds))
+    }
+}
+
+/// [`Keybinds`] as written, before leaving out what this version doesn't know
+#[derive(Deserialize)]
+#[serde(rename = "Keybinds")]
+struct WrittenKeybinds(BTreeMap<ActionName, Vec<String>>);
+
+/// Key of the keybindings map, `None` for an action this version doesn't have
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+struct ActionName(Option<InputAction>);
+
+impl<'de> Deserialize<'de> for ActionName {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        struct ActionNameVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for ActionNameVisitor {
+            type Value = ActionName;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("the name of an action")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, name: &str) -> Result<ActionName, E> {
+                let action = InputAction::ALL
+                    .into_iter()
+                    .find(|action| format!("{action:?}") == name);
+                Ok(ActionName(action))
+            }
+        }
+
+        deserializer.deserialize_identifier(ActionNameVisitor)
+    }
+}
+
+impl Default for Keybinds {
+    fn default() -> Self {
+        Self(
+            InputAction::ALL
+                .iter()
+                .map(|action| (*action, action.default_chords()))
+                .collect(),
+        )
+    }
+}
+
+impl Keybinds {
+    pub fn chords(&self, action: InputAction) -> &[KeyChord] {
+        self.0
+            .get(&action)
+            .map(|chords| chords.as_slice())
+            .unwrap_or(&[])
+    }
+
+    /// Actions added since the settings file was written get their default chords
+    pub fn fill_missing(&mut self) {
+        for action in InputAction::ALL {
+            self.0
+                .entry(action)
+                .or_insert_with(|| action.default_chords());
+        }
+    }
+
+    /// Other actions that share one of this action's chords
+    pub fn conflicts(&self, action: InputAction) -> Vec<InputAction> {
+        let chords = self.chords(action);
+        self.0
+            .iter()
+            .filter(|(other, other_chords)| {
+                **other != action && other_chords.iter().any(|chord| chords.contains(chord))
+            })
+            .map(|(other, _)| *other)
+            .collect()
+    }
+}
+
+/// Action that the next key pressed gets bound to, nothing else reacts to keys in the meantime
+#[derive(Resource, Default, Debug)]
+pub struct Rebinding(pub Option<InputAction>);
+
+/// Which actions are held and which were just pressed this frame. Nothing is pressed while typing
+/// into a text box, or while typing an exact value during a gizmo drag
+#[derive(Resource, Default, Debug)]
+pub struct ActionState {
+    pressed: HashSet<InputAction>,
+    just_pressed: HashSet<InputAction>,
+}
+
+impl ActionState {
+    pub fn pressed(&self, action: InputAction) -> bool {
+        self.pressed.contains(&action)
+    }
+
+    pub fn just_pressed(&self, action: InputAction) -> bool {
+        self.just_pressed.contains(&action)
+    }
+}
+
+fn update_action_state(
+    mut action_state: ResMut<ActionState>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    settings: Res<Settings>,
+    rebinding: Res<Rebinding>,
+    numeric_entry: Res<NumericEntry>,
+    mut contexts: EguiContexts,
+) {
+    action_state.pressed.clear();
+    action_state.just_pressed.clear();
+    if rebinding.0.is_some() || contexts.ctx_mut().wants_keyboard_input() {
+        return;
+    }
+    // Keys typed during a gizmo drag are an exact value, so only the ones that still mean
+    // something to the drag get through
+    let typing_value = numeric_entry.dragging();
+    let modifiers = Modifiers::pressed(&keyboard);
+    for (action, chords) in settings.keybinds.0.iter() {
+        if typing_value
+            && !matches!(
+                action,
+                InputAction::Cancel | InputAction::TypeValue | InputAction::DisableSnapping
+            )
+        {
+            continue;
+        }
+        for chord in chords.iter().filter(|chord| chord.matches(modifiers)) {
+            if keyboard.pressed(chord.key) {
+                action_state.pressed.insert(*action);
+            }
+            if keyboard.just_pressed(chord.key) {
+                action_state.just_pressed.insert(*action);
+            }
+        }
+    }
+}
+
+/// The key (and modifiers) pressed this frame, for binding it to an action. A modifier counts
+/// once it's let go of without another key being pressed
+pub fn captured_chord(keyboard: &ButtonInput<KeyCode>) -> Option<KeyChord> {
+    if let Some(key) = keyboard
+        .get_just_pressed()
+        .find(|key| !is_modifier(**key) && key_name(**key).is_some())
+    {
+        return Some(KeyChord {
+            key: *key,
+            modifiers: Modifiers::pressed(keyboard),
+        });
+    }
+    keyboard
+        .get_just_released()
+        .find(|key| is_modifier(**key) && key_name(**key).is_some())
+        .map(|key| KeyChord::key(*key))
+}
+
+fn key_name(key: KeyCode) -> Option<&'static str> {
+    KEY_NAMES
+        .iter()
+        .find(|(other, _)| *other == key)
+        .map(|(_, name)| *name)
+}
+
+/// Keys that can be bound, with the names they're saved as
+const KEY_NAMES: &[(KeyCode, &str)] = &[
+    (KeyCode::KeyA, "A"),
+    (KeyCode::KeyB, "B"),
+    (KeyCode::KeyC, "C"),
+    (KeyCode::KeyD, "D"),
+    (KeyCode::KeyE, "E"),
+    (KeyCode::KeyF, "F"),
+    (KeyCode::KeyG, "G"),
+    (KeyCode::KeyH, "H"),
+    (KeyCode::KeyI, "I"),
+    (KeyCode::KeyJ, "J"),
+    (KeyCode::KeyK, "K"),
+    (KeyCode::KeyL, "L"),
+    (KeyCode::KeyM, "M"),
+    (KeyCode::KeyN, "N"),
+    (KeyCode::KeyO, "O"),
+    (KeyCode::KeyP, "P"),
+    (KeyCode::KeyQ, "Q"),
+    (KeyCode::KeyR, "R"),
+    (KeyCode::KeyS, "S"),
+    (KeyCode::KeyT, "T"),
+    (KeyCode::KeyU, "U"),
+    (KeyCode::KeyV, "V"),
+    (KeyCode::KeyW, "W"),
+    (KeyCode::KeyX, "X"),
+    (KeyCode::KeyY, "Y"),
+    (KeyCode::KeyZ, "Z"),
+    (KeyCode::Digit0, "0"),
+    (KeyCode::Digit1, "1"),
+    (KeyCode::Digit2, "2"),
+    (KeyCode::Digit3, "3"),
+    (KeyCode::Digit4, "4"),
+    (KeyCode::Digit5, "5"),
+    (KeyCode::Digit6, "6"),
+    (KeyCode::Digit7, "7"),
+    (KeyCode::Digit8, "8"),
+    (KeyCode::Digit9, "9"),
+    (KeyCode::Numpad0, "Numpad0"),
+    (KeyCode::Numpad1, "Numpad1"),
+    (KeyCode::Numpad2, "Numpad2"),
+    (KeyCode::Numpad3, "Numpad3"),
+    (KeyCode::Numpad4, "Numpad4"),
+    (KeyCode::Numpad5, "Numpad5"),
+    (KeyCode::Numpad6, "Numpad6"),
+    (KeyCode::Numpad7, "Numpad7"),
+    (KeyCode::Numpad8, "Numpad8"),
+    (KeyCode::Numpad9, "Numpad9"),
+    (KeyCode::NumpadDecimal, "NumpadDecimal"),
+    (KeyCode::NumpadAdd, "NumpadAdd"),
+    (KeyCode::NumpadSubtract, "NumpadSubtract"),
+    (KeyCode::NumpadEnter, "NumpadEnter"),
+    (KeyCode::F1, "F1"),
+    (KeyCode::F2, "F2"),
+    (KeyCode::F3, "F3"),
+    (KeyCode::F4, "F4"),
+    (KeyCode::F5, "F5"),
+    (KeyCode::F6, "F6"),
+    (KeyCode::F7, "F7"),
+    (KeyCode::F8, "F8"),
+    (KeyCode::F9, "F9"),
+    (KeyCode::F10, "F10"),
+    (KeyCode::F11, "F11"),
+    (KeyCode::F12, "F12"),
+    (KeyCode::Escape, "Escape"),
+    (KeyCode::Tab, "Tab"),
+    (KeyCode::Space, "Space"),
+    (KeyCode::Enter, "Enter"),
+    (KeyCode::Backspace, "Backspace"),
+    (KeyCode::Delete, "Delete"),
+    (KeyCode::Insert, "Insert"),
+    (KeyCode::Home, "Home"),
+    (KeyCode::End, "End"),
+    (KeyCode::PageUp, "PageUp"),
+    (KeyCode::PageDown, "PageDown"),
+    (KeyCode::ArrowUp, "Up"),
+    (KeyCode::ArrowDown, "Down"),
+    (KeyCode::ArrowLeft, "Left"),
+    (KeyCode::ArrowRight, "Right"),
+    (KeyCode::Comma, "Comma"),
+    (KeyCode::Period, "Period"),
+    (KeyCode::Slash, "Slash"),
+    (KeyCode::Backslash, "Backslash"),
+    (KeyCode::Semicolon, "Semicolon"),
+    (KeyCode::Quote, "Quote"),
+    (KeyCode::BracketLeft, "BracketLeft"),
+    (KeyCode::BracketRight, "BracketRight"),
+    (KeyCode::Minus, "Minus"),
+    (KeyCode::Equal, "Equal"),
+    (KeyCode::Backquote, "Backquote"),
+    (KeyCode::ShiftLeft, "ShiftLeft"),
+    (KeyCode::ShiftRight, "ShiftRight"),
+    (KeyCode::ControlLeft, "ControlLeft"),
+    (KeyCode::ControlRight, "ControlRight"),
+    (KeyCode::AltLeft, "AltLeft"),
+    (KeyCode::AltRight, "AltRight"),
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chords_parse_from_text() {
+        let chord: KeyChord = "Ctrl+Shift+G".parse().unwrap();
+        assert_eq!(chord, KeyChord::key(KeyCode::KeyG).ctrl().shift());
+        assert_eq!("Numpad1".parse(), Ok(KeyChord::key(KeyCode::Numpad1)));
+        assert_eq!(
+            " Alt + Up ".parse::<KeyChord>().unwrap().key,
+            KeyCode::ArrowUp
+        );
+    }
+
+    #[test]
+    fn unknown_keys_and_modifiers_dont_parse() {
+        assert!("Hyper+G".parse::<KeyChord>().is_err());
+        assert!("Ctrl+NotAKey".parse::<KeyChord>().is_err());
+        assert!("".parse::<KeyChord>().is_err());
+    }
+
+    #[test]
+    fn chords_round_trip_through_text() {
+        for chord in InputAction::ALL
+            .iter()
+            .flat_map(|action| action.default_chords())
+        {
+            assert_eq!(chord.to_string().parse(), Ok(chord));
+        }
+    }
+
+    #[test]
+    fn modifiers_have_to_match_exactly() {
+        let ctrl = Modifiers {
+            ctrl: true,
+            ..default()
+        };
+        assert!(KeyChord::key(KeyCode::KeyG).matches(Modifiers::default()));
+        assert!(!KeyChord::key(KeyCode::KeyG).matches(ctrl));
+        assert!(KeyChord::key(KeyCode::KeyG).ctrl().matches(ctrl));
+        // Holding Ctrl doesn't stop Shift from turning snapping off
+        assert!(KeyChord::key(KeyCode::ShiftLeft).matches(ctrl));
+    }
+
+    #[test]
+    fn every_action_has_a_default_without_conflicts() {
+        let keybinds = Keybinds::default();
+        for action in InputAction::ALL {
+            assert!(
+                !keybinds.chords(action).is_empty(),
+                "{action:?} has no keys"
+            );
+            assert!(
+                keybinds.conflicts(action).is_empty(),
+                "{action:?} conflicts with {:?}",
+                keybinds.conflicts(action)
+            );
+        }
+    }
+
+    #[test]
+    fn missing_actions_get_their_defaults() {
+        let mut keybinds: Keybinds = ron::from_str("({Undo: [\"Ctrl+Y\"]})").unwrap();
+        keybinds.fill_missing();
+        assert_eq!(
+            keybinds.chords(InputAction::Undo),
+            ["Ctrl+Y".parse::<KeyChord>().unwrap()]
+        );
+        assert_eq!(
+            keybinds.chords(InputAction::Save),
+            InputAction::Save.default_chords()
+        );
+    }
+
+    #[test]
+    fn actions_and_keys_from_newer_versions_are_left_out() {
+        let mut keybinds: Keybinds = ron::from_str(
+            "({Undo: [\"Ctrl+Y\"], FlyToTheMoon: [\"Ctrl+M\"], \
+             Save: [\"Ctrl+Warp\", \"Ctrl+S\"], Delete: [\"Hyper+X\"]})",
+        )
+        .unwrap();
+        assert!(!keybinds.0.contains_key(&InputAction::Delete));
+        keybinds.fill_missing();
+        let chord = |text: &str| text.parse::<KeyChord>().unwrap();
+        assert_eq!(keybinds.chords(InputAction::Undo), [chord("Ctrl+Y")]);
+        assert_eq!(keybinds.chords(InputAction::Save), [chord("Ctrl+S")]);
+        assert_eq!(
+            keybinds.chords(InputAction::Delete),
+            InputAction::Delete.default_chords()
+        );
+        assert_eq!(keybinds.0.len(), InputAction::ALL.len());
+    }
+
+    #[test]
+    fn keybinds_round_trip_through_ron() {
+        let keybinds = Keybinds::default();
+        let text = ron::to_string(&keybinds).unwrap();
+        assert_eq!(ron::from_str::<Keybinds>(&text).unwrap(), keybinds);
+    }
+}
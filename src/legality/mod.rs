@@ -0,0 +1,323 @@
+use std::collections::BTreeSet;
+
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::{
+    bounds::{world_bounds, BoundsQuery},
+    catalog::{find_by_scene_path, PartCategory},
+    placing::{CurrentlyPlacing, PartName},
+    settings::Settings,
+    units::LengthUnit,
+};
+
+mod ui;
+use ui::LegalityUiPlugin;
+
+pub struct LegalityPlugin;
+
+impl Plugin for LegalityPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<RuleSets>()
+            .init_resource::<LegalityReport>()
+            .add_plugins(LegalityUiPlugin)
+            .add_systems(Startup, load_rule_sets);
+    }
+}
+
+/// One rule set per competition (and season), so new rules only need a new file
+pub const RULES_FOLDER: &str = "assets/rules";
+
+/// What a robot has to follow to be legal
+#[derive(Deserialize, Debug, Clone)]
+pub struct RuleSet {
+    pub name: String,
+    /// Largest the robot can be, in inches. It can be turned any way to fit
+    pub max_size: Vec3,
+    #[serde(default)]
+    pub limits: Vec<Limit>,
+    /// Whether parts that aren't from VEX (like placeholder models) are allowed
+    #[serde(default)]
+    pub allow_non_vex: bool,
+}
+
+/// A cap on how many of some kind of part can be used. A part counts if it's in one of the
+/// categories or has one of the names
+#[derive(Deserialize, Debug, Clone)]
+pub struct Limit {
+    pub label: String,
+    #[serde(default)]
+    pub categories: Vec<PartCategory>,
+    #[serde(default)]
+    pub parts: Vec<String>,
+    pub max: usize,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct RuleSets(pub Vec<RuleSet>);
+
+impl RuleSets {
+    /// The rule set chosen in the settings, or the first one if that one is gone
+    pub fn selected(&self, settings: &Settings) -> Option<&RuleSet> {
+        self.0
+            .iter()
+            .find(|rule_set| rule_set.name == settings.rule_set)
+            .or(self.0.first())
+    }
+}
+
+fn load_rule_sets(mut rule_sets: ResMut<RuleSets>) {
+    let Ok(entries) = std::fs::read_dir(RULES_FOLDER) else {
+        warn!("Couldn't find the {RULES_FOLDER} folder, robots can't be checked");
+        return;
+    };
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|extension| extension == "ron"))
+        .collect();
+    paths.sort();
+    for path in paths {
+        let rule_set = std::fs::read_to_string(&path)
+            .map_err(|error| error.to_string())
+            .and_then(|contents| {
+                ron::from_str::<RuleSet>(&contents).map_err(|error| error.to_string())
+            });
+        match rule_set {
+            Ok(rule_set) => rule_sets.0.push(rule_set),
+            Err(error) => warn!("Couldn't read rules {}: {error}", path.display()),
+        }
+    }
+}
+
+/// The result of checking one rule
+#[derive(Debug, Clone)]
+pub struct RuleCheck {
+    pub label: String,
+    pub detail: String,
+    pub passed: bool,
+    /// Parts that break the rule, so they can be selected
+    pub parts: Vec<Entity>,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct LegalityReport {
+    pub checks: Vec<RuleCheck>,
+}
+
+impl LegalityReport {
+    pub fn passed(&self) -> bool {
+        self.checks.iter().all(|check| check.passed)
+    }
+}
+
+/// The six ways a box can be turned so its sides line up with the axes, as which of the
+/// robot's sizes ends up along x, y (up) and z
+const ORIENTATIONS: [([usize; 3], &str); 6] = [
+    ([0, 1, 2], "as placed"),
+    ([2, 1, 0], "turned sideways"),
+    ([1, 0, 2], "on its side"),
+    ([2, 0, 1], "on its side, turned"),
+    ([0, 2, 1], "on its front"),
+    ([1, 2, 0], "on its front, turned"),
+];
+
+/// Checks every visible part against the rules
+fn check_robot(
+    rule_set: &RuleSet,
+    part_query: &Query<(Entity, &PartName, &InheritedVisibility), Without<CurrentlyPlacing>>,
+    children_query: &Query<&Children>,
+    aabb_query: &BoundsQuery,
+    settings: &Settings,
+) -> Vec<RuleCheck> {
+    let parts: Vec<(Entity, &str)> = part_query
+        .iter()
+        .filter(|(_, _, visibility)| visibility.get())
+        .map(|(entity, part_name, _)| (entity, part_name.0.as_str()))
+        .collect();
+    let bounds = parts
+        .iter()
+        .filter_map(|(entity, _)| world_bounds(*entity, children_query, aabb_query))
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)));
+    let size = bounds.map_or(Vec3::ZERO, |(min, max)| max - min);
+    check_parts(rule_set, &parts, size, settings.unit)
+}
+
+/// The rules themselves, for parts (by scene path) that all fit in a box of `size`
+fn check_parts(
+    rule_set: &RuleSet,
+    parts: &[(Entity, &str)],
+    size: Vec3,
+    unit: LengthUnit,
+) -> Vec<RuleCheck> {
+    let mut checks = Vec::new();
+    let size_text = |size: Vec3| {
+        format!(
+            "{} × {} × {}",
+            unit.format(size.x),
+            unit.format(size.y),
+            unit.format(size.z)
+        )
+    };
+    let fits = ORIENTATIONS.iter().find(|(axes, _)| {
+        let turned = Vec3::new(size[axes[0]], size[axes[1]], size[axes[2]]);
+        turned.cmple(rule_set.max_size).all()
+    });
+    checks.push(RuleCheck {
+        label: format!("Fits in {}", size_text(rule_set.max_size)),
+        detail: match fits {
+            Some((_, orientation)) => format!("{} ({orientation})", size_text(size)),
+            None => format!("{} doesn't fit any way up", size_text(size)),
+        },
+        passed: fits.is_some(),
+        parts: Vec::new(),
+    });
+
+    for limit in &rule_set.limits {
+        let counted: Vec<Entity> = parts
+            .iter()
+            .filter(|(_, part_name)| {
+                find_by_scene_path(part_name).is_some_and(|entry| {
+                    limit.categories.contains(&entry.category)
+                        || limit.parts.iter().any(|name| name == entry.name)
+                })
+            })
+            .map(|(entity, _)| *entity)
+            .collect();
+        let passed = counted.len() <= limit.max;
+        checks.push(RuleCheck {
+            label: format!("At most {} {}", limit.max, limit.label),
+            detail: format!("{} used", counted.len()),
+            passed,
+            parts: if passed { Vec::new() } else { counted },
+        });
+    }
+
+    if !rule_set.allow_non_vex {
+        let mut names = BTreeSet::new();
+        let non_vex: Vec<Entity> = parts
+            .iter()
+            .filter(|(_, part_name)| {
+                let entry = find_by_scene_path(part_name);
+                if entry.is_some_and(|entry| entry.vex_part) {
+                    return false;
+                }
+                names.insert(entry.map_or(*part_name, |entry| entry.name));
+                true
+            })
+            .map(|(entity, _)| *entity)
+            .collect();
+        checks.push(RuleCheck {
+            label: "Only VEX parts".to_string(),
+            detail: if non_vex.is_empty() {
+                "No other parts used".to_string()
+            } else {
+                names.into_iter().collect::<Vec<_>>().join(", ")
+            },
+            passed: non_vex.is_empty(),
+            parts: non_vex,
+        });
+    }
+    checks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule_set(limits: Vec<Limit>, allow_non_vex: bool) -> RuleSet {
+        RuleSet {
+            name: "Test".to_string(),
+            max_size: Vec3::new(18.0, 18.0, 18.0),
+            limits,
+            allow_non_vex,
+        }
+    }
+
+    fn parts(names: &[&'static str]) -> Vec<(Entity, &'static str)> {
+        names
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (Entity::from_raw(index as u32), *name))
+            .collect()
+    }
+
+    const C_CHANNEL: &str = "models/c-channel-1x2x1x1.glb#Scene0";
+    const DUCK: &str = "models/low_poly_duck.glb#Scene0";
+
+    #[test]
+    fn robots_can_be_turned_to_fit() {
+        let rules = RuleSet {
+            max_size: Vec3::new(24.0, 12.0, 12.0),
+            ..rule_set(Vec::new(), true)
+        };
+        let parts = parts(&[C_CHANNEL]);
+        let checks = check_parts(
+            &rules,
+            &parts,
+            Vec3::new(10.0, 20.0, 5.0),
+            LengthUnit::Inches,
+        );
+        assert!(checks[0].passed);
+        assert!(checks[0].detail.ends_with("(on its side)"));
+        let too_big = check_parts(
+            &rules,
+            &parts,
+            Vec3::new(25.0, 1.0, 1.0),
+            LengthUnit::Inches,
+        );
+        assert!(!too_big[0].passed);
+    }
+
+    #[test]
+    fn limits_count_by_category_and_name() {
+        let rules = rule_set(
+            vec![
+                Limit {
+                    label: "structure".to_string(),
+                    categories: vec![PartCategory::Structure],
+                    parts: Vec::new(),
+                    max: 1,
+                },
+                Limit {
+                    label: "ducks".to_string(),
+                    categories: Vec::new(),
+                    parts: vec!["Duck".to_string()],
+                    max: 1,
+                },
+            ],
+            true,
+        );
+        let parts = parts(&[C_CHANNEL, C_CHANNEL, DUCK]);
+        let checks = check_parts(&rules, &parts, Vec3::ONE, LengthUnit::Inches);
+        let structure = &checks[1];
+        assert!(!structure.passed);
+        assert_eq!(structure.detail, "2 used");
+        assert_eq!(structure.parts, vec![parts[0].0, parts[1].0]);
+        let ducks = &checks[2];
+        assert!(ducks.passed);
+        assert!(ducks.parts.is_empty());
+    }
+
+    #[test]
+    fn placeholders_break_vex_only_rules() {
+        let parts = parts(&[C_CHANNEL, DUCK, "models/gone.glb#Scene0"]);
+        let checks = check_parts(
+            &rule_set(Vec::new(), false),
+            &parts,
+            Vec3::ONE,
+            LengthUnit::Inches,
+        );
+        let vex_only = checks.last().unwrap();
+        assert!(!vex_only.passed);
+        assert_eq!(vex_only.parts, vec![parts[1].0, parts[2].0]);
+        assert_eq!(vex_only.detail, "Duck, models/gone.glb#Scene0");
+        let allowed = check_parts(
+            &rule_set(Vec::new(), true),
+            &parts,
+            Vec3::ONE,
+            LengthUnit::Inches,
+        );
+        assert_eq!(allowed.len(), 1);
+    }
+}
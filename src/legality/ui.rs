@@ -0,0 +1,150 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    assembly::Assembly,
+    bounds::BoundsQuery,
+    keybinds::{ActionState, InputAction},
+    move_objects::{select_parts, CurrentlyMoving, MoveObjectsState},
+    placing::{CurrentlyPlacing, PartName},
+    settings::Settings,
+};
+
+use super::{check_robot, LegalityReport, RuleSets, RULES_FOLDER};
+
+pub struct LegalityUiPlugin;
+
+impl Plugin for LegalityUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LegalityWindow>().add_systems(
+            Update,
+            (
+                toggle_legality_window,
+                (update_report, legality_window)
+                    .chain()
+                    .run_if(|window: Res<LegalityWindow>| window.open),
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+struct LegalityWindow {
+    open: bool,
+}
+
+fn toggle_legality_window(actions: Res<ActionState>, mut window: ResMut<LegalityWindow>) {
+    if actions.just_pressed(InputAction::Legality) {
+        window.open = !window.open;
+    }
+}
+
+/// Checked every frame while the report is open, so it follows along as the robot changes
+fn update_report(
+    mut report: ResMut<LegalityReport>,
+    rule_sets: Res<RuleSets>,
+    settings: Res<Settings>,
+    part_query: Query<(Entity, &PartName, &InheritedVisibility), Without<CurrentlyPlacing>>,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+) {
+    report.checks = match rule_sets.selected(&settings) {
+        Some(rule_set) => check_robot(
+            rule_set,
+            &part_query,
+            &children_query,
+            &aabb_query,
+            &settings,
+        ),
+        None => Vec::new(),
+    };
+}
+
+fn legality_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut window: ResMut<LegalityWindow>,
+    report: Res<LegalityReport>,
+    rule_sets: Res<RuleSets>,
+    mut settings: ResMut<Settings>,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    parent_query: Query<&Parent>,
+    assembly_query: Query<(), With<Assembly>>,
+    mut moving_state: ResMut<NextState<MoveObjectsState>>,
+) {
+    let mut open = true;
+    let mut selected = None;
+    egui::Window::new("Robot check")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            let Some(current) = rule_sets
+                .selected(&settings)
+                .map(|rules| rules.name.clone())
+            else {
+                ui.label(format!("No rules found in {RULES_FOLDER}"));
+                return;
+            };
+            egui::ComboBox::from_label("Rules")
+                .selected_text(&current)
+                .show_ui(ui, |ui| {
+                    for rule_set in &rule_sets.0 {
+                        if ui
+                            .selectable_label(rule_set.name == current, &rule_set.name)
+                            .clicked()
+                        {
+                            settings.rule_set = rule_set.name.clone();
+                            settings.write_changes();
+                        }
+                    }
+                });
+            ui.separator();
+
+            egui::Grid::new("Rule checks")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (index, check) in report.checks.iter().enumerate() {
+                        if check.passed {
+                            ui.colored_label(egui::Color32::GREEN, "✔");
+                        } else {
+                            ui.colored_label(egui::Color32::RED, "✖");
+                        }
+                        ui.label(&check.label);
+                        ui.horizontal(|ui| {
+                            ui.label(&check.detail);
+                            if !check.parts.is_empty()
+                                && ui
+                                    .small_button("Select")
+                                    .on_hover_text("Select the parts that break this rule")
+                                    .clicked()
+                            {
+                                selected = Some(index);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+            if report.passed() {
+                ui.colored_label(egui::Color32::GREEN, "Legal");
+            } else {
+                ui.colored_label(egui::Color32::RED, "Not legal");
+            }
+        });
+    if !open {
+        window.open = false;
+    }
+
+    if let Some(check) = selected.and_then(|index| report.checks.get(index)) {
+        select_parts(
+            &mut commands,
+            check.parts.iter().copied(),
+            &selected_query,
+            &parent_query,
+            &assembly_query,
+            &mut moving_state,
+        );
+    }
+}
@@ -42,7 +42,56 @@ use actions::ActionsPlugin;
 mod screenshot;
 use screenshot::ScreenshotPlugin;
 
+mod pattern;
+use pattern::PatternPlugin;
+
+mod mirror;
+use mirror::MirrorPlugin;
+
+mod assembly;
+use assembly::AssemblyPlugin;
+
+mod catalog;
+
+mod bounds;
+
+mod units;
+
+mod keybinds;
+use keybinds::KeybindsPlugin;
+
+mod properties;
+use properties::PropertiesPlugin;
+
+mod views;
+use views::ViewsPlugin;
+
+mod measure;
+use measure::MeasurePlugin;
+
+mod interference;
+use interference::InterferencePlugin;
+
+mod legality;
+use legality::LegalityPlugin;
+
+mod mass;
+use mass::MassPlugin;
+
+mod bom;
+use bom::BomPlugin;
+
+mod inventory;
+use inventory::InventoryPlugin;
+
+mod export;
+use export::ExportPlugin;
+
 fn main() {
+    if bom::run_from_command_line() {
+        return;
+    }
+
     App::new()
         .insert_resource(ClearColor(Color::hex("333333").unwrap()))
         .insert_resource(Msaa::Sample4)
@@ -75,9 +124,29 @@ fn main() {
             BlurRegionsPlugin::default(),
             bevy_framepace::FramepacePlugin,
         ))
+        .add_plugins((
+            PatternPlugin,
+            MirrorPlugin,
+            AssemblyPlugin,
+            PropertiesPlugin,
+            KeybindsPlugin,
+            ViewsPlugin,
+            MeasurePlugin,
+            InterferencePlugin,
+            LegalityPlugin,
+            MassPlugin,
+            BomPlugin,
+            InventoryPlugin,
+            ExportPlugin,
+        ))
         .add_systems(Startup, setup)
-        .add_systems(Startup, set_frame_cap)
-        .add_systems(Update, toggle_grid_visibility)
+        .add_systems(
+            Update,
+            (
+                toggle_grid_visibility,
+                set_frame_cap.run_if(resource_changed::<Settings>),
+            ),
+        )
         .run();
 }
 
@@ -146,9 +215,9 @@ fn setup(
 
 fn toggle_grid_visibility(
     mut visibility_query: Query<&mut Visibility, With<InfiniteGrid>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<keybinds::ActionState>,
 ) {
-    if keyboard.just_pressed(KeyCode::Equal) {
+    if actions.just_pressed(keybinds::InputAction::ToggleGrid) {
         let mut visibility = visibility_query.get_single_mut().unwrap();
         match *visibility {
             Visibility::Visible => {
@@ -161,14 +230,15 @@ fn toggle_grid_visibility(
     }
 }
 
+/// An FPS cap of 0 means no cap
 fn set_frame_cap(
     mut settings: ResMut<bevy_framepace::FramepaceSettings>,
-    _loaded_settings: Res<crate::settings::Settings>,
+    loaded_settings: Res<Settings>,
 ) {
-    // let limit = loaded_settings.fps_cap;
-    // if limit == 0.0 {
-    settings.limiter = Limiter::Off;
-    // } else {
-    //     settings.limiter = Limiter::from_framerate(loaded_settings.fps_cap);
-    // }
+    let limit = loaded_settings.fps_cap;
+    settings.limiter = if limit <= 0.0 {
+        Limiter::Off
+    } else {
+        Limiter::from_framerate(limit)
+    };
 }
@@ -0,0 +1,313 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    bounds::{world_bounds, world_triangles, BoundsQuery, MeshQuery},
+    catalog::find_by_scene_path,
+    move_objects::CurrentlyMoving,
+    placing::{CurrentlyPlacing, PartName},
+};
+
+mod ui;
+use ui::MassUiPlugin;
+
+pub struct MassPlugin;
+
+impl Plugin for MassPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MassCache>()
+            .init_resource::<MassProperties>()
+            .add_plugins(MassUiPlugin);
+    }
+}
+
+/// Mass, center of gravity and inertia of one part or a group of them. Masses are in pounds,
+/// lengths in inches and the inertia tensor (lb·in²) is about `center`, along the world axes
+#[derive(Debug, Clone, Copy)]
+pub struct MassData {
+    pub mass: f32,
+    pub center: Vec3,
+    pub inertia: Mat3,
+}
+
+impl MassData {
+    /// Combines parts by moving each inertia tensor to the shared center of gravity
+    pub fn combine(parts: &[MassData]) -> Option<MassData> {
+        let mass: f32 = parts.iter().map(|part| part.mass).sum();
+        if mass <= 0.0 {
+            return None;
+        }
+        let center = parts
+            .iter()
+            .map(|part| part.center * part.mass)
+            .sum::<Vec3>()
+            / mass;
+        let inertia = parts.iter().fold(Mat3::ZERO, |inertia, part| {
+            let offset = part.center - center;
+            let parallel_axis =
+                Mat3::from_diagonal(Vec3::splat(offset.length_squared())) - outer(offset, offset);
+            inertia + part.inertia + parallel_axis * part.mass
+        });
+        Some(MassData {
+            mass,
+            center,
+            inertia,
+        })
+    }
+}
+
+/// What the mass window shows, for the selection or the whole robot
+#[derive(Resource, Default, Debug)]
+pub struct MassProperties {
+    pub total: Option<MassData>,
+    pub parts: usize,
+    pub selection_only: bool,
+}
+
+/// Mass data of each part, worked out again only when the part moves
+#[derive(Resource, Default, Debug)]
+struct MassCache(HashMap<Entity, (GlobalTransform, MassData)>);
+
+fn outer(a: Vec3, b: Vec3) -> Mat3 {
+    Mat3::from_cols(a * b.x, a * b.y, a * b.z)
+}
+
+/// Volume, centroid and second moment (covariance) about the centroid of a closed mesh with a
+/// density of 1, by adding up a tetrahedron from `origin` to each triangle
+fn mesh_volume(triangles: &[[Vec3; 3]], origin: Vec3) -> Option<(f32, Vec3, Mat3)> {
+    // Covariance of the tetrahedron with corners at the origin and the three unit axes
+    let canonical =
+        Mat3::from_cols_array(&[2.0, 1.0, 1.0, 1.0, 2.0, 1.0, 1.0, 1.0, 2.0]) * (1.0 / 120.0);
+
+    let mut volume = 0.0;
+    let mut first_moment = Vec3::ZERO;
+    let mut covariance = Mat3::ZERO;
+    for triangle in triangles {
+        let corners = Mat3::from_cols(
+            triangle[0] - origin,
+            triangle[1] - origin,
+            triangle[2] - origin,
+        );
+        let determinant = corners.determinant();
+        volume += determinant / 6.0;
+        first_moment += determinant / 24.0 * (corners.x_axis + corners.y_axis + corners.z_axis);
+        covariance += corners * canonical * corners.transpose() * determinant;
+    }
+    // Models with their triangles wound the other way come out inside out
+    if volume < 0.0 {
+        volume = -volume;
+        first_moment = -first_moment;
+        covariance = -covariance;
+    }
+    if volume < 1e-6 {
+        return None;
+    }
+    let centroid = first_moment / volume;
+    Some((
+        volume,
+        origin + centroid,
+        covariance - outer(centroid, centroid) * volume,
+    ))
+}
+
+fn inertia_from_covariance(covariance: Mat3) -> Mat3 {
+    let trace = covariance.x_axis.x + covariance.y_axis.y + covariance.z_axis.z;
+    Mat3::from_diagonal(Vec3::splat(trace)) - covariance
+}
+
+/// Mass data of one part from its model, using the catalog's mass when there is one. Models that
+/// aren't closed (or haven't loaded) are treated as a solid box the size of their bounds
+fn part_mass(
+    part: Entity,
+    part_name: &PartName,
+    children_query: &Query<&Children>,
+    aabb_query: &BoundsQuery,
+    mesh_query: &MeshQuery,
+    meshes: &Assets<Mesh>,
+) -> Option<MassData> {
+    let entry = find_by_scene_path(&part_name.0)?;
+    let (min, max) = world_bounds(part, children_query, aabb_query)?;
+    let triangles = world_triangles(part, children_query, mesh_query, meshes);
+    let (volume, center, covariance) = match mesh_volume(&triangles, (min + max) / 2.0) {
+        Some(shape) => shape,
+        None => {
+            let size = max - min;
+            let volume = size.x * size.y * size.z;
+            let box_covariance = Mat3::from_diagonal(size * size * volume / 12.0);
+            (volume, (min + max) / 2.0, box_covariance)
+        }
+    };
+    let mass = entry.mass.unwrap_or(volume * entry.material.density());
+    let density = if volume > 0.0 { mass / volume } else { 0.0 };
+    Some(MassData {
+        mass,
+        center,
+        inertia: inertia_from_covariance(covariance) * density,
+    })
+}
+
+/// Adds up the selection (including parts inside selected assemblies), or every visible part when
+/// nothing is selected
+fn update_mass_properties(
+    mut properties: ResMut<MassProperties>,
+    mut cache: ResMut<MassCache>,
+    part_query: Query<
+        (Entity, &PartName, &GlobalTransform, &InheritedVisibility),
+        Without<CurrentlyPlacing>,
+    >,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    (aabb_query, mesh_query): (BoundsQuery, MeshQuery),
+    meshes: Res<Assets<Mesh>>,
+) {
+    cache.0.retain(|entity, _| part_query.contains(*entity));
+
+    let is_selected = |part: Entity| {
+        selected_query.contains(part)
+            || parent_query
+                .iter_ancestors(part)
+                .any(|ancestor| selected_query.contains(ancestor))
+    };
+    let selection_only = part_query.iter().any(|(entity, ..)| is_selected(entity));
+
+    let mut parts = Vec::new();
+    for (entity, part_name, global_transform, visibility) in part_query.iter() {
+        if !visibility.get() || (selection_only && !is_selected(entity)) {
+            continue;
+        }
+        let cached = cache
+            .0
+            .get(&entity)
+            .filter(|(cached_transform, _)| cached_transform == global_transform)
+            .map(|(_, mass)| *mass);
+        let mass = cached.or_else(|| {
+            let mass = part_mass(
+                entity,
+                part_name,
+                &children_query,
+                &aabb_query,
+                &mesh_query,
+                &meshes,
+            )?;
+            cache.0.insert(entity, (*global_transform, mass));
+            Some(mass)
+        });
+        parts.extend(mass);
+    }
+
+    *properties = MassProperties {
+        total: MassData::combine(&parts),
+        parts: parts.len(),
+        selection_only,
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: Mat3, b: Mat3) {
+        assert!(a.abs_diff_eq(b, 1e-4), "{a} != {b}");
+    }
+
+    /// Box from `min` to `max` with its triangles wound outwards
+    fn box_triangles(min: Vec3, max: Vec3) -> Vec<[Vec3; 3]> {
+        let corner = |x: bool, y: bool, z: bool| {
+            Vec3::new(
+                if x { max.x } else { min.x },
+                if y { max.y } else { min.y },
+                if z { max.z } else { min.z },
+            )
+        };
+        let faces = [
+            [0b000, 0b010, 0b110, 0b100], // -z
+            [0b001, 0b101, 0b111, 0b011], // +z
+            [0b000, 0b100, 0b101, 0b001], // -y
+            [0b010, 0b011, 0b111, 0b110], // +y
+            [0b000, 0b001, 0b011, 0b010], // -x
+            [0b100, 0b110, 0b111, 0b101], // +x
+        ];
+        faces
+            .iter()
+            .flat_map(|face| {
+                let [a, b, c, d] = face
+                    .map(|bits: u8| corner(bits & 0b100 != 0, bits & 0b010 != 0, bits & 1 != 0));
+                [[a, b, c], [a, c, d]]
+            })
+            .collect()
+    }
+
+    #[test]
+    fn box_volume_center_and_inertia() {
+        let triangles = box_triangles(Vec3::new(1.0, 0.0, 0.0), Vec3::new(3.0, 1.0, 4.0));
+        let (volume, center, covariance) = mesh_volume(&triangles, Vec3::ZERO).unwrap();
+        assert!((volume - 8.0).abs() < 1e-4);
+        assert!(center.abs_diff_eq(Vec3::new(2.0, 0.5, 2.0), 1e-4));
+        // A solid box has I = m (b² + c²) / 12 about each axis
+        let size = Vec3::new(2.0, 1.0, 4.0);
+        let expected = Mat3::from_diagonal(
+            Vec3::new(
+                size.y * size.y + size.z * size.z,
+                size.x * size.x + size.z * size.z,
+                size.x * size.x + size.y * size.y,
+            ) * volume
+                / 12.0,
+        );
+        assert_close(inertia_from_covariance(covariance), expected);
+    }
+
+    #[test]
+    fn volume_doesnt_depend_on_the_origin_or_winding() {
+        let triangles = box_triangles(Vec3::ZERO, Vec3::ONE);
+        let inside_out: Vec<_> = triangles.iter().map(|[a, b, c]| [*a, *c, *b]).collect();
+        let (volume, center, covariance) = mesh_volume(&triangles, Vec3::ZERO).unwrap();
+        for (origin, triangles) in [(Vec3::splat(5.0), &triangles), (Vec3::ZERO, &inside_out)] {
+            let (other_volume, other_center, other_covariance) =
+                mesh_volume(triangles, origin).unwrap();
+            assert!((volume - other_volume).abs() < 1e-4);
+            assert!(center.abs_diff_eq(other_center, 1e-4));
+            assert_close(covariance, other_covariance);
+        }
+    }
+
+    #[test]
+    fn flat_meshes_have_no_volume() {
+        let triangle = [Vec3::ZERO, Vec3::X, Vec3::Y];
+        assert!(mesh_volume(
+            &[triangle, [triangle[0], triangle[2], triangle[1]]],
+            Vec3::Z
+        )
+        .is_none());
+    }
+
+    #[test]
+    fn combining_moves_inertia_to_the_shared_center() {
+        let point = |mass: f32, center: Vec3| MassData {
+            mass,
+            center,
+            inertia: Mat3::ZERO,
+        };
+        let combined =
+            MassData::combine(&[point(1.0, Vec3::new(-1.0, 0.0, 0.0)), point(3.0, Vec3::X)])
+                .unwrap();
+        assert_eq!(combined.mass, 4.0);
+        assert!(combined.center.abs_diff_eq(Vec3::new(0.5, 0.0, 0.0), 1e-6));
+        // 1 lb 1.5 in and 3 lb 0.5 in from the center, around the y and z axes
+        assert_close(
+            combined.inertia,
+            Mat3::from_diagonal(Vec3::new(0.0, 3.0, 3.0)),
+        );
+
+        let spinning = MassData {
+            inertia: Mat3::IDENTITY,
+            ..point(2.0, Vec3::ONE)
+        };
+        let alone = MassData::combine(&[spinning]).unwrap();
+        assert_close(alone.inertia, Mat3::IDENTITY);
+    }
+
+    #[test]
+    fn nothing_to_combine() {
+        assert!(MassData::combine(&[]).is_none());
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    keybinds::{ActionState, InputAction},
+    settings::Settings,
+};
+
+use super::{update_mass_properties, MassProperties};
+
+pub struct MassUiPlugin;
+
+impl Plugin for MassUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MassWindow>().add_systems(
+            Update,
+            (
+                toggle_mass_window,
+                (update_mass_properties, mass_window, draw_center_of_gravity)
+                    .chain()
+                    .run_if(|window: Res<MassWindow>| window.open),
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+const CENTER_OF_GRAVITY_COLOR: Color = Color::rgb(0.2, 0.9, 1.0);
+
+const POUND_IN_KILOGRAMS: f32 = 0.453_592_37;
+
+#[derive(Resource, Debug)]
+struct MassWindow {
+    open: bool,
+    show_marker: bool,
+}
+
+impl Default for MassWindow {
+    fn default() -> Self {
+        Self {
+            open: false,
+            show_marker: true,
+        }
+    }
+}
+
+fn toggle_mass_window(actions: Res<ActionState>, mut window: ResMut<MassWindow>) {
+    if actions.just_pressed(InputAction::MassProperties) {
+        window.open = !window.open;
+    }
+}
+
+fn mass_window(
+    mut contexts: EguiContexts,
+    mut window: ResMut<MassWindow>,
+    properties: Res<MassProperties>,
+    settings: Res<Settings>,
+) {
+    let mut open = true;
+    egui::Window::new("Mass")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(if properties.selection_only {
+                format!("Selection ({} parts)", properties.parts)
+            } else {
+                format!("Whole robot ({} parts)", properties.parts)
+            });
+            let Some(total) = properties.total else {
+                ui.label("Nothing to weigh");
+                return;
+            };
+            ui.separator();
+
+            let unit = settings.unit;
+            egui::Grid::new("Mass properties")
+                .num_columns(2)
+                .show(ui, |ui| {
+                    ui.label("Mass");
+                    ui.label(format!(
+                        "{:.2} lb ({:.2} kg)",
+                        total.mass,
+                        total.mass * POUND_IN_KILOGRAMS
+                    ));
+                    ui.end_row();
+
+                    ui.label("Center of gravity");
+                    ui.label(format!(
+                        "x {}  y {}  z {}",
+                        unit.format(total.center.x),
+                        unit.format(total.center.y),
+                        unit.format(total.center.z)
+                    ));
+                    ui.end_row();
+
+                    ui.label("Height off the ground");
+                    ui.label(unit.format(total.center.y));
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.label("Inertia about the center of gravity (lb·in²)");
+            egui::Grid::new("Inertia tensor")
+                .num_columns(3)
+                .striped(true)
+                .show(ui, |ui| {
+                    for row in 0..3 {
+                        for column in 0..3 {
+                            ui.monospace(format!("{:>10.3}", total.inertia.col(column)[row]));
+                        }
+                        ui.end_row();
+                    }
+                });
+            ui.separator();
+            ui.checkbox(&mut window.show_marker, "Show center of gravity");
+        });
+    if !open {
+        window.open = false;
+    }
+}
+
+/// A ball at the center of gravity with a line down to the ground, to see how far it is from
+/// the edge of the robot
+fn draw_center_of_gravity(
+    mut gizmos: Gizmos,
+    window: Res<MassWindow>,
+    properties: Res<MassProperties>,
+) {
+    let Some(total) = properties.total.filter(|_| window.show_marker) else {
+        return;
+    };
+    let center = total.center;
+    gizmos.sphere(center, Quat::IDENTITY, 0.3, CENTER_OF_GRAVITY_COLOR);
+    for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+        gizmos.line(
+            center - axis * 0.6,
+            center + axis * 0.6,
+            CENTER_OF_GRAVITY_COLOR,
+        );
+    }
+    let ground = Vec3::new(center.x, 0.0, center.z);
+    gizmos.line(center, ground, CENTER_OF_GRAVITY_COLOR);
+    gizmos.circle(ground, Direction3d::Y, 0.3, CENTER_OF_GRAVITY_COLOR);
+}
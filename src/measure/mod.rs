@@ -0,0 +1,549 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_infinite_grid::InfiniteGrid;
+use bevy_mod_raycast::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    bounds::{world_triangles, MeshQuery},
+    constraints::ConstrainComponent,
+    keybinds::{ActionState, InputAction},
+    move_objects::{CurrentlyMoving, MoveObjectsState},
+    placing::{find_part_root, PartName, PlacingState},
+    saving::SAVE_FILE,
+    units::{LengthUnit, HOLE_PITCH},
+};
+
+mod ui;
+use ui::MeasureUiPlugin;
+
+pub struct MeasurePlugin;
+
+impl Plugin for MeasurePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<MeasureState>()
+            .init_resource::<Measurements>()
+            .init_resource::<MeasureTool>()
+            .add_plugins(MeasureUiPlugin)
+            .add_systems(Startup, load_measurements)
+            .add_systems(OnEnter(MeasureState::Measuring), enter_measure)
+            .add_systems(OnExit(MeasureState::Measuring), exit_measure)
+            .add_systems(
+                Update,
+                (
+                    toggle_measure,
+                    pick_measure_point.run_if(in_state(MeasureState::Measuring)),
+                )
+                    .run_if(in_state(crate::ui::UIState::Editor)),
+            );
+    }
+}
+
+/// While measuring, clicks pick points instead of selecting parts
+#[derive(States, Default, Debug, Hash, Eq, PartialEq, Clone, Copy)]
+pub enum MeasureState {
+    Measuring,
+    #[default]
+    NotMeasuring,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MeasureKind {
+    /// Between any two points, the ground included
+    #[default]
+    Distance,
+    /// Between the holes closest to the two clicks, counted in holes
+    Holes,
+    /// Between two faces
+    Angle,
+    /// Between the axes of the holes closest to the two clicks
+    AxisAngle,
+    /// The smallest gap between two parts
+    Clearance,
+}
+
+impl MeasureKind {
+    pub const ALL: [MeasureKind; 5] = [
+        Self::Distance,
+        Self::Holes,
+        Self::Angle,
+        Self::AxisAngle,
+        Self::Clearance,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Distance => "Distance",
+            Self::Holes => "Holes",
+            Self::Angle => "Angle",
+            Self::AxisAngle => "Axis angle",
+            Self::Clearance => "Clearance",
+        }
+    }
+
+    pub fn instructions(&self) -> &'static str {
+        match self {
+            Self::Distance => "Click two points",
+            Self::Holes => "Click near a hole on each part",
+            Self::Angle => "Click two faces",
+            Self::AxisAngle => "Click near a hole on each part",
+            Self::Clearance => "Click two parts",
+        }
+    }
+}
+
+/// A measurement, drawn in the viewport as a dimension between two points
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Measurement {
+    pub kind: MeasureKind,
+    pub from: Vec3,
+    pub to: Vec3,
+    /// Normals of the two faces for angles, or axes of the two holes for axis angles
+    pub directions: Option<(Vec3, Vec3)>,
+    pub visible: bool,
+}
+
+impl Measurement {
+    pub fn text(&self, unit: LengthUnit) -> String {
+        let distance = self.from.distance(self.to);
+        match self.kind {
+            MeasureKind::Distance => unit.format(distance),
+            MeasureKind::Holes if unit == LengthUnit::Holes => unit.format(distance),
+            MeasureKind::Holes => format!(
+                "{:.1} holes ({})",
+                distance / HOLE_PITCH,
+                unit.format(distance)
+            ),
+            MeasureKind::Angle => {
+                let (a, b) = self.directions.unwrap_or((Vec3::Y, Vec3::Y));
+                format!("{:.1}°", a.angle_between(b).to_degrees())
+            }
+            // A hole goes both ways, so the angle between two of them is at most 90°
+            MeasureKind::AxisAngle => {
+                let (a, b) = self.directions.unwrap_or((Vec3::Y, Vec3::Y));
+                let cos = a.normalize_or_zero().dot(b.normalize_or_zero()).abs();
+                format!("{:.1}° between axes", cos.min(1.0).acos().to_degrees())
+            }
+            MeasureKind::Clearance => format!("Clearance {}", unit.format(distance)),
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub struct Measurements {
+    pub list: Vec<Measurement>,
+    /// Hides every measurement at once
+    pub visible: bool,
+}
+
+impl Default for Measurements {
+    fn default() -> Self {
+        Self {
+            list: Vec::new(),
+            visible: true,
+        }
+    }
+}
+
+/// A clicked point, waiting for the second one
+#[derive(Debug, Clone, Copy)]
+pub struct MeasurePick {
+    pub point: Vec3,
+    /// The face's normal, or the hole's axis when the click was moved to a hole
+    pub normal: Vec3,
+    pub part: Option<Entity>,
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct MeasureTool {
+    pub kind: MeasureKind,
+    pub first: Option<MeasurePick>,
+}
+
+fn toggle_measure(
+    actions: Res<ActionState>,
+    measure_state: Res<State<MeasureState>>,
+    mut next_measure_state: ResMut<NextState<MeasureState>>,
+    mut measurements: ResMut<Measurements>,
+) {
+    if actions.just_pressed(InputAction::ToggleMeasurements) {
+        measurements.visible = !measurements.visible;
+    }
+    let measuring = *measure_state.get() == MeasureState::Measuring;
+    if actions.just_pressed(InputAction::Measure)
+        || (measuring && actions.just_pressed(InputAction::Cancel))
+    {
+        next_measure_state.set(if measuring {
+            MeasureState::NotMeasuring
+        } else {
+            MeasureState::Measuring
+        });
+    }
+}
+
+/// The gizmo would get in the way of clicking, so the selection is cleared
+fn enter_measure(
+    mut commands: Commands,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    mut moving_state: ResMut<NextState<MoveObjectsState>>,
+    mut placing_state: ResMut<NextState<PlacingState>>,
+    mut measurements: ResMut<Measurements>,
+) {
+    for entity in selected_query.iter() {
+        commands.entity(entity).remove::<CurrentlyMoving>();
+    }
+    moving_state.set(MoveObjectsState::NotMoving);
+    placing_state.set(PlacingState::PlacingDisabled);
+    measurements.visible = true;
+}
+
+fn exit_measure(mut placing_state: ResMut<NextState<PlacingState>>, mut tool: ResMut<MeasureTool>) {
+    placing_state.set(PlacingState::NotPlacing);
+    tool.first = None;
+}
+
+fn pick_measure_point(
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    cursor_ray: Res<CursorRay>,
+    mut raycast: Raycast,
+    grid_query: Query<(), With<InfiniteGrid>>,
+    part_query: Query<(), With<PartName>>,
+    parent_query: Query<&Parent>,
+    hole_query: Query<(&GlobalTransform, &Parent), With<ConstrainComponent>>,
+    (children_query, mesh_query): (Query<&Children>, MeshQuery),
+    meshes: Res<Assets<Mesh>>,
+    mut tool: ResMut<MeasureTool>,
+    mut measurements: ResMut<Measurements>,
+) {
+    if !mouse.just_pressed(MouseButton::Left) || contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+    let Some(cursor_ray) = **cursor_ray else {
+        return;
+    };
+    // Only plain distances can start or end on the ground
+    let parts_only = tool.kind != MeasureKind::Distance;
+    let Some((hit_entity, point, normal)) = raycast
+        .cast_ray(
+            cursor_ray,
+            &RaycastSettings {
+                filter: &|entity| !(parts_only && grid_query.contains(entity)),
+                ..default()
+            },
+        )
+        .first()
+        .map(|(entity, intersection)| (*entity, intersection.position(), intersection.normal()))
+    else {
+        return;
+    };
+    let part = find_part_root(hit_entity, &parent_query, |entity| {
+        part_query.contains(entity)
+    });
+
+    // Hole markers are cylinders, standing along their Y axis
+    let (point, normal) = if matches!(tool.kind, MeasureKind::Holes | MeasureKind::AxisAngle) {
+        let Some(closest_hole) = hole_query
+            .iter()
+            .filter(|(_, parent)| Some(parent.get()) == part)
+            .map(|(hole_transform, _)| (hole_transform.translation(), hole_transform.up()))
+            .min_by(|(a, _), (b, _)| {
+                a.distance_squared(point)
+                    .total_cmp(&b.distance_squared(point))
+            })
+        else {
+            return;
+        };
+        closest_hole
+    } else {
+        (point, normal)
+    };
+    let pick = MeasurePick {
+        point,
+        normal,
+        part,
+    };
+
+    let Some(first) = tool.first.take() else {
+        tool.first = Some(pick);
+        return;
+    };
+    let (from, to) = match tool.kind {
+        MeasureKind::Clearance => {
+            let (Some(first_part), Some(second_part)) = (first.part, pick.part) else {
+                return;
+            };
+            if first_part == second_part {
+                tool.first = Some(first);
+                return;
+            }
+            let first_triangles =
+                world_triangles(first_part, &children_query, &mesh_query, &meshes);
+            let second_triangles =
+                world_triangles(second_part, &children_query, &mesh_query, &meshes);
+            let Some(closest) = closest_points(&first_triangles, &second_triangles) else {
+                return;
+            };
+            closest
+        }
+        _ => (first.point, pick.point),
+    };
+    measurements.list.push(Measurement {
+        kind: tool.kind,
+        from,
+        to,
+        directions: matches!(tool.kind, MeasureKind::Angle | MeasureKind::AxisAngle)
+            .then_some((first.normal, pick.normal)),
+        visible: true,
+    });
+}
+
+/// The closest points between two parts, on their triangles. Triangles are taken nearest first
+/// and any whose bounding box is already farther away than the closest pair so far is skipped, so
+/// only the few facing each other are measured exactly
+fn closest_points(a: &[[Vec3; 3]], b: &[[Vec3; 3]]) -> Option<(Vec3, Vec3)> {
+    let with_bounds = |triangles: &[[Vec3; 3]]| -> Vec<([Vec3; 3], (Vec3, Vec3))> {
+        triangles
+            .iter()
+            .map(|triangle| (*triangle, triangle_bounds(triangle)))
+            .collect()
+    };
+    let mut a = with_bounds(a);
+    let mut b = with_bounds(b);
+    let part_bounds = |triangles: &[([Vec3; 3], (Vec3, Vec3))]| {
+        triangles
+            .iter()
+            .map(|(_, bounds)| *bounds)
+            .reduce(|a, b| (a.0.min(b.0), a.1.max(b.1)))
+    };
+    let (bounds_a, bounds_b) = (part_bounds(&a)?, part_bounds(&b)?);
+    a.sort_by(|(_, first), (_, second)| {
+        box_distance(*first, bounds_b).total_cmp(&box_distance(*second, bounds_b))
+    });
+    b.sort_by(|(_, first), (_, second)| {
+        box_distance(*first, bounds_a).total_cmp(&box_distance(*second, bounds_a))
+    });
+
+    let (mut closest_distance, mut closest) = (f32::INFINITY, None);
+    for (triangle_a, box_a) in &a {
+        // Sorted, so the rest are at least as far away
+        if box_distance(*box_a, bounds_b) >= closest_distance {
+            break;
+        }
+        for (triangle_b, box_b) in &b {
+            if box_distance(*box_b, bounds_a) >= closest_distance {
+                break;
+            }
+            if box_distance(*box_a, *box_b) >= closest_distance {
+                continue;
+            }
+            let (point_a, point_b) = closest_points_on_triangles(triangle_a, triangle_b);
+            let distance = point_a.distance(point_b);
+            if distance < closest_distance {
+                closest_distance = distance;
+                closest = Some((point_a, point_b));
+            }
+        }
+    }
+    closest
+}
+
+fn triangle_bounds(triangle: &[Vec3; 3]) -> (Vec3, Vec3) {
+    (
+        triangle[0].min(triangle[1]).min(triangle[2]),
+        triangle[0].max(triangle[1]).max(triangle[2]),
+    )
+}
+
+/// The gap between two boxes (min, max), zero if they overlap
+fn box_distance(a: (Vec3, Vec3), b: (Vec3, Vec3)) -> f32 {
+    (a.0 - b.1).max(b.0 - a.1).max(Vec3::ZERO).length()
+}
+
+/// Closest points between two triangles that don't cross each other. They're either a corner of
+/// one and a point on the other, or a point on an edge of each
+fn closest_points_on_triangles(a: &[Vec3; 3], b: &[Vec3; 3]) -> (Vec3, Vec3) {
+    let edges = |triangle: &[Vec3; 3]| {
+        [
+            (triangle[0], triangle[1]),
+            (triangle[1], triangle[2]),
+            (triangle[2], triangle[0]),
+        ]
+    };
+    let edges_b = edges(b);
+    a.iter()
+        .map(|corner| (*corner, closest_point_on_triangle(*corner, b)))
+        .chain(
+            b.iter()
+                .map(|corner| (closest_point_on_triangle(*corner, a), *corner)),
+        )
+        .chain(edges(a).into_iter().flat_map(|edge_a| {
+            edges_b
+                .into_iter()
+                .map(move |edge_b| closest_points_on_segments(edge_a, edge_b))
+        }))
+        .min_by(|(a, b), (c, d)| a.distance_squared(*b).total_cmp(&c.distance_squared(*d)))
+        .unwrap()
+}
+
+/// After Ericson, Real-Time Collision Detection 5.1.5: works out which corner, edge or the face
+/// of the triangle is closest from how the point lines up with its edges
+fn closest_point_on_triangle(point: Vec3, &[a, b, c]: &[Vec3; 3]) -> Vec3 {
+    let ab = b - a;
+    let ac = c - a;
+    let ap = point - a;
+    let d1 = ab.dot(ap);
+    let d2 = ac.dot(ap);
+    if d1 <= 0.0 && d2 <= 0.0 {
+        return a;
+    }
+    let bp = point - b;
+    let d3 = ab.dot(bp);
+    let d4 = ac.dot(bp);
+    if d3 >= 0.0 && d4 <= d3 {
+        return b;
+    }
+    let vc = d1 * d4 - d3 * d2;
+    if vc <= 0.0 && d1 >= 0.0 && d3 <= 0.0 {
+        return a + ab * (d1 / (d1 - d3));
+    }
+    let cp = point - c;
+    let d5 = ab.dot(cp);
+    let d6 = ac.dot(cp);
+    if d6 >= 0.0 && d5 <= d6 {
+        return c;
+    }
+    let vb = d5 * d2 - d1 * d6;
+    if vb <= 0.0 && d2 >= 0.0 && d6 <= 0.0 {
+        return a + ac * (d2 / (d2 - d6));
+    }
+    let va = d3 * d6 - d5 * d4;
+    if va <= 0.0 && d4 - d3 >= 0.0 && d5 - d6 >= 0.0 {
+        return b + (c - b) * ((d4 - d3) / ((d4 - d3) + (d5 - d6)));
+    }
+    let area = va + vb + vc;
+    // Flat triangles have no face, their edges are measured on their own
+    if area <= 0.0 {
+        return a;
+    }
+    a + ab * (vb / area) + ac * (vc / area)
+}
+
+/// After Ericson, Real-Time Collision Detection 5.1.9
+fn closest_points_on_segments(
+    (start_a, end_a): (Vec3, Vec3),
+    (start_b, end_b): (Vec3, Vec3),
+) -> (Vec3, Vec3) {
+    let along_a = end_a - start_a;
+    let along_b = end_b - start_b;
+    let between = start_a - start_b;
+    let length_a = along_a.length_squared();
+    let length_b = along_b.length_squared();
+    let f = along_b.dot(between);
+    let (s, t) = if length_a <= f32::EPSILON && length_b <= f32::EPSILON {
+        (0.0, 0.0)
+    } else if length_a <= f32::EPSILON {
+        (0.0, (f / length_b).clamp(0.0, 1.0))
+    } else {
+        let c = along_a.dot(between);
+        if length_b <= f32::EPSILON {
+            ((-c / length_a).clamp(0.0, 1.0), 0.0)
+        } else {
+            let b = along_a.dot(along_b);
+            let denominator = length_a * length_b - b * b;
+            // Parallel edges are closest anywhere along their overlap, the start of a does
+            let s = if denominator > 0.0 {
+                ((b * f - c * length_b) / denominator).clamp(0.0, 1.0)
+            } else {
+                0.0
+            };
+            let t = (b * s + f) / length_b;
+            if t < 0.0 {
+                ((-c / length_a).clamp(0.0, 1.0), 0.0)
+            } else if t > 1.0 {
+                (((b - c) / length_a).clamp(0.0, 1.0), 1.0)
+            } else {
+                (s, t)
+            }
+        }
+    };
+    (start_a + along_a * s, start_b + along_b * t)
+}
+
+/// Only the measurements are read back from the project file, the parts in it are left alone
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProjectMeasurements {
+    measurements: Vec<Measurement>,
+}
+
+fn load_measurements(mut measurements: ResMut<Measurements>) {
+    let Ok(contents) = std::fs::read_to_string(SAVE_FILE) else {
+        return;
+    };
+    if let Ok(project) = ron::from_str::<ProjectMeasurements>(&contents) {
+        measurements.list = project.measurements;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triangle(points: [[f32; 3]; 3]) -> [Vec3; 3] {
+        points.map(Vec3::from_array)
+    }
+
+    #[test]
+    fn clearance_reaches_the_middle_of_faces() {
+        // A corner pointing down at the middle of a big triangle, far from any of its corners
+        let floor = triangle([[-10.0, 0.0, -10.0], [10.0, 0.0, -10.0], [0.0, 0.0, 10.0]]);
+        let above = triangle([[0.0, 0.25, 0.0], [-1.0, 2.0, 0.0], [1.0, 2.0, 0.0]]);
+        let (from, to) = closest_points(&[floor], &[above]).unwrap();
+        assert!(from.distance(Vec3::ZERO) < 1e-5);
+        assert!(to.distance(Vec3::new(0.0, 0.25, 0.0)) < 1e-5);
+    }
+
+    #[test]
+    fn clearance_between_crossed_edges() {
+        let a = triangle([[-1.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, -1.0, 0.0]]);
+        let b = triangle([[0.0, 0.5, -1.0], [0.0, 0.5, 1.0], [0.0, 1.5, 0.0]]);
+        let (from, to) = closest_points(&[a], &[b]).unwrap();
+        assert!((from.distance(to) - 0.5).abs() < 1e-5);
+        assert!(from.distance(Vec3::ZERO) < 1e-5);
+    }
+
+    #[test]
+    fn clearance_picks_the_closest_of_many() {
+        let far = triangle([[5.0, 0.0, 0.0], [6.0, 0.0, 0.0], [5.0, 1.0, 0.0]]);
+        let near = triangle([[1.0, 0.0, 0.0], [2.0, 0.0, 0.0], [1.0, 1.0, 0.0]]);
+        let other = triangle([[-1.0, 0.0, 0.0], [0.0, 0.0, 0.0], [-1.0, 1.0, 0.0]]);
+        let (from, to) = closest_points(&[far, near], &[other]).unwrap();
+        assert!((from.distance(to) - 1.0).abs() < 1e-5);
+        assert_eq!(closest_points(&[], &[other]), None);
+    }
+
+    #[test]
+    fn axis_angle_ignores_which_way_the_holes_face() {
+        let measurement = |a: Vec3, b: Vec3| Measurement {
+            kind: MeasureKind::AxisAngle,
+            from: Vec3::ZERO,
+            to: Vec3::X,
+            directions: Some((a, b)),
+            visible: true,
+        };
+        assert_eq!(
+            measurement(Vec3::Y, Vec3::NEG_Y).text(LengthUnit::Inches),
+            "0.0° between axes"
+        );
+        assert_eq!(
+            measurement(Vec3::Y, Vec3::X).text(LengthUnit::Inches),
+            "90.0° between axes"
+        );
+        let tilted = Vec3::new(1.0, -1.0, 0.0);
+        assert_eq!(
+            measurement(Vec3::Y, tilted).text(LengthUnit::Inches),
+            "45.0° between axes"
+        );
+    }
+}
@@ -0,0 +1,136 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::settings::Settings;
+
+use super::{MeasureKind, MeasureState, MeasureTool, Measurements};
+
+pub struct MeasureUiPlugin;
+
+impl Plugin for MeasureUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                measure_window.run_if(in_state(MeasureState::Measuring)),
+                draw_measurements,
+            )
+                .run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+const MEASURE_COLOR: Color = Color::rgb(1.0, 0.8, 0.1);
+
+fn measure_window(
+    mut contexts: EguiContexts,
+    mut tool: ResMut<MeasureTool>,
+    mut measurements: ResMut<Measurements>,
+    mut measure_state: ResMut<NextState<MeasureState>>,
+    settings: Res<Settings>,
+) {
+    let mut open = true;
+    egui::Window::new("Measure")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                for kind in MeasureKind::ALL {
+                    if ui.radio_value(&mut tool.kind, kind, kind.label()).changed() {
+                        tool.first = None;
+                    }
+                }
+            });
+            ui.label(if tool.first.is_some() {
+                "Now click the second one"
+            } else {
+                tool.kind.instructions()
+            });
+            ui.separator();
+
+            ui.checkbox(&mut measurements.visible, "Show measurements");
+            let mut removed = None;
+            for (index, measurement) in measurements.list.iter_mut().enumerate() {
+                ui.horizontal(|ui| {
+                    let text = measurement.text(settings.unit);
+                    ui.checkbox(&mut measurement.visible, text);
+                    if ui.small_button("×").on_hover_text("Delete").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+            if let Some(index) = removed {
+                measurements.list.remove(index);
+            }
+            if !measurements.list.is_empty() && ui.button("Delete all").clicked() {
+                measurements.list.clear();
+            }
+        });
+    if !open {
+        measure_state.set(MeasureState::NotMeasuring);
+    }
+}
+
+/// Draws each measurement as a dimension line with its value in the middle
+fn draw_measurements(
+    mut contexts: EguiContexts,
+    mut gizmos: Gizmos,
+    measurements: Res<Measurements>,
+    tool: Res<MeasureTool>,
+    measure_state: Res<State<MeasureState>>,
+    camera_query: Query<(&Camera, &GlobalTransform), With<Camera3d>>,
+    settings: Res<Settings>,
+) {
+    if *measure_state.get() == MeasureState::Measuring {
+        if let Some(first) = tool.first {
+            gizmos.sphere(first.point, Quat::IDENTITY, 0.08, MEASURE_COLOR);
+        }
+    }
+    if !measurements.visible {
+        return;
+    }
+    let Ok((camera, camera_transform)) = camera_query.get_single() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    for (index, measurement) in measurements
+        .list
+        .iter()
+        .enumerate()
+        .filter(|(_, measurement)| measurement.visible)
+    {
+        let (from, to) = (measurement.from, measurement.to);
+        match (measurement.kind, measurement.directions) {
+            (MeasureKind::Angle, Some((first_normal, second_normal))) => {
+                gizmos.arrow(from, from + first_normal, MEASURE_COLOR);
+                gizmos.arrow(to, to + second_normal, MEASURE_COLOR);
+                gizmos.line(from, to, MEASURE_COLOR.with_a(0.4));
+            }
+            (MeasureKind::AxisAngle, Some((first_axis, second_axis))) => {
+                gizmos.line(from - first_axis, from + first_axis, MEASURE_COLOR);
+                gizmos.line(to - second_axis, to + second_axis, MEASURE_COLOR);
+                gizmos.line(from, to, MEASURE_COLOR.with_a(0.4));
+            }
+            _ => {
+                gizmos.line(from, to, MEASURE_COLOR);
+                for end in [from, to] {
+                    gizmos.sphere(end, Quat::IDENTITY, 0.04, MEASURE_COLOR);
+                }
+            }
+        }
+
+        let Some(position) = camera.world_to_viewport(camera_transform, (from + to) / 2.0) else {
+            continue;
+        };
+        egui::Area::new(egui::Id::new(("Measurement", index)))
+            .fixed_pos(egui::pos2(position.x, position.y))
+            .pivot(egui::Align2::CENTER_CENTER)
+            .order(egui::Order::Background)
+            .interactable(false)
+            .show(ctx, |ui| {
+                egui::Frame::popup(ui.style()).show(ui, |ui| {
+                    ui.label(measurement.text(settings.unit));
+                });
+            });
+    }
+}
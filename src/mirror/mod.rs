@@ -0,0 +1,148 @@
+use std::collections::HashMap;
+
+use bevy::prelude::*;
+
+use crate::{
+    actions::{Action, ActionList},
+    constraints::{ConstraintData, Mates, PendingConstraints},
+    move_objects::CurrentlyMoving,
+    placing::{Part, PartName},
+};
+
+mod ui;
+pub use ui::MirrorState;
+use ui::MirrorUiPlugin;
+
+pub struct MirrorPlugin;
+
+impl Plugin for MirrorPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MirrorEvent>()
+            .add_plugins(MirrorUiPlugin)
+            .add_systems(
+                Update,
+                mirror_selection
+                    .run_if(on_event::<MirrorEvent>())
+                    .run_if(in_state(crate::ui::UIState::Editor)),
+            );
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct MirrorPlane {
+    pub point: Vec3,
+    pub normal: Vec3,
+}
+
+impl MirrorPlane {
+    /// Splits the robot into its left and right halves
+    pub const WORLD_YZ: Self = Self {
+        point: Vec3::ZERO,
+        normal: Vec3::X,
+    };
+
+    pub fn reflect_point(&self, point: Vec3) -> Vec3 {
+        let normal = self.normal.normalize();
+        point - 2.0 * (point - self.point).dot(normal) * normal
+    }
+
+    pub fn reflect_vector(&self, vector: Vec3) -> Vec3 {
+        let normal = self.normal.normalize();
+        vector - 2.0 * vector.dot(normal) * normal
+    }
+
+    /// A reflection flips handedness, which a rotation can't represent, so the part's local X
+    /// axis gets flipped back. Parts that are symmetric about their local YZ plane look exactly
+    /// mirrored, any other part comes out turned around rather than as its mirror image
+    pub fn reflect_rotation(&self, rotation: Quat) -> Quat {
+        let x = -self.reflect_vector(rotation * Vec3::X);
+        let y = self.reflect_vector(rotation * Vec3::Y);
+        let z = self.reflect_vector(rotation * Vec3::Z);
+        Quat::from_mat3(&Mat3::from_cols(x, y, z)).normalize()
+    }
+
+    pub fn reflect_transform(&self, transform: Transform) -> Transform {
+        Transform {
+            translation: self.reflect_point(transform.translation),
+            rotation: self.reflect_rotation(transform.rotation),
+            scale: transform.scale,
+        }
+    }
+}
+
+impl Default for MirrorPlane {
+    fn default() -> Self {
+        Self::WORLD_YZ
+    }
+}
+
+/// Mirrors every selected part across the plane
+#[derive(Event, Debug, Clone, Copy)]
+pub struct MirrorEvent(pub MirrorPlane);
+
+fn mirror_selection(
+    mut commands: Commands,
+    mut events: EventReader<MirrorEvent>,
+    selected_query: Query<(Entity, &Transform, &PartName, &Handle<Scene>), With<CurrentlyMoving>>,
+    mut mates: ResMut<Mates>,
+    mut action_list: ResMut<ActionList>,
+) {
+    for MirrorEvent(plane) in events.read() {
+        let mut mirrored: HashMap<Entity, Entity> = HashMap::new();
+        for (entity, transform, part_name, scene) in selected_query.iter() {
+            // Only the placement gets reflected, the model itself can't be
+            if !crate::catalog::find_by_scene_path(&part_name.0)
+                .is_some_and(|entry| entry.symmetric)
+            {
+                warn!(
+                    "{} isn't symmetric, so its mirrored copy is turned around instead of reflected",
+                    part_name.0
+                );
+            }
+            let copy = commands
+                .spawn((
+                    SceneBundle {
+                        scene: scene.clone(),
+                        transform: plane.reflect_transform(*transform),
+                        ..default()
+                    },
+                    Part,
+                    PartName(part_name.0.clone()),
+                    PendingConstraints,
+                ))
+                .id();
+            mirrored.insert(entity, copy);
+        }
+        if mirrored.is_empty() {
+            continue;
+        }
+
+        // Mates between two mirrored parts get recreated between their copies
+        let mirrored_mates: Vec<_> = mates
+            .0
+            .iter()
+            .filter_map(|mate| {
+                let parents = [
+                    *mirrored.get(&mate.parents[0])?,
+                    *mirrored.get(&mate.parents[1])?,
+                ];
+                let constraints = mate.constraints.map(|constraint| ConstraintData {
+                    transform: Transform {
+                        translation: plane.reflect_vector(constraint.transform.translation),
+                        rotation: plane.reflect_rotation(constraint.transform.rotation),
+                        ..constraint.transform
+                    },
+                });
+                Some(crate::constraints::ConstraintEvent {
+                    constraints,
+                    parents,
+                })
+            })
+            .collect();
+        mates.0.extend(mirrored_mates);
+
+        action_list
+            .0
+            .push(Action::Mirrored(mirrored.into_values().collect()));
+    }
+}
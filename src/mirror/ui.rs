@@ -0,0 +1,191 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_infinite_grid::InfiniteGrid;
+use bevy_mod_raycast::prelude::*;
+
+use crate::{
+    keybinds::{ActionState, InputAction},
+    move_objects::CurrentlyMoving,
+};
+
+use super::{MirrorEvent, MirrorPlane};
+
+pub struct MirrorUiPlugin;
+
+impl Plugin for MirrorUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<MirrorState>()
+            .init_resource::<MirrorEditor>()
+            .add_systems(
+                Update,
+                (
+                    open_mirror_window,
+                    mirror_window,
+                    draw_mirror_plane,
+                    pick_mirror_plane.run_if(in_state(MirrorState::PickingPlane)),
+                )
+                    .run_if(in_state(crate::ui::UIState::Editor)),
+            );
+    }
+}
+
+/// While picking a plane, clicks go to the picked face instead of selecting parts
+#[derive(States, Default, Debug, Hash, Eq, PartialEq, Clone, Copy)]
+pub enum MirrorState {
+    PickingPlane,
+    #[default]
+    NotPicking,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PlaneChoice {
+    WorldYZ,
+    WorldXZ,
+    WorldXY,
+    Picked(MirrorPlane),
+}
+
+#[derive(Resource, Debug)]
+struct MirrorEditor {
+    open: bool,
+    choice: PlaneChoice,
+    /// Offset of a world plane along its normal
+    offset: f32,
+}
+
+impl Default for MirrorEditor {
+    fn default() -> Self {
+        Self {
+            open: false,
+            choice: PlaneChoice::WorldYZ,
+            offset: 0.0,
+        }
+    }
+}
+
+impl MirrorEditor {
+    fn plane(&self) -> MirrorPlane {
+        let normal = match self.choice {
+            PlaneChoice::WorldYZ => Vec3::X,
+            PlaneChoice::WorldXZ => Vec3::Y,
+            PlaneChoice::WorldXY => Vec3::Z,
+            PlaneChoice::Picked(plane) => return plane,
+        };
+        MirrorPlane {
+            point: normal * self.offset,
+            normal,
+        }
+    }
+}
+
+fn open_mirror_window(
+    actions: Res<ActionState>,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+    mut editor: ResMut<MirrorEditor>,
+) {
+    if !actions.just_pressed(InputAction::Mirror) {
+        return;
+    }
+    editor.open = !editor.open && !selected_query.is_empty();
+}
+
+fn mirror_window(
+    mut contexts: EguiContexts,
+    mut editor: ResMut<MirrorEditor>,
+    mut mirror_state: ResMut<NextState<MirrorState>>,
+    mut mirror_events: EventWriter<MirrorEvent>,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+) {
+    if !editor.open {
+        return;
+    }
+    let mut open = true;
+    egui::Window::new("Mirror")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(format!(
+                "{} part(s) selected",
+                selected_query.iter().count()
+            ));
+            ui.separator();
+
+            let choice = &mut editor.choice;
+            ui.radio_value(choice, PlaneChoice::WorldYZ, "World YZ (left/right)");
+            ui.radio_value(choice, PlaneChoice::WorldXZ, "World XZ (top/bottom)");
+            ui.radio_value(choice, PlaneChoice::WorldXY, "World XY (front/back)");
+            ui.horizontal(|ui| {
+                let picked = matches!(editor.choice, PlaneChoice::Picked(_));
+                ui.add_enabled(false, egui::RadioButton::new(picked, "Picked face"));
+                if ui.button("Pick face").clicked() {
+                    mirror_state.set(MirrorState::PickingPlane);
+                }
+            });
+
+            if !matches!(editor.choice, PlaneChoice::Picked(_)) {
+                ui.horizontal(|ui| {
+                    ui.label("Offset");
+                    ui.add(egui::DragValue::new(&mut editor.offset).speed(0.05));
+                });
+            }
+
+            ui.separator();
+            if ui
+                .add_enabled(!selected_query.is_empty(), egui::Button::new("Mirror"))
+                .clicked()
+            {
+                mirror_events.send(MirrorEvent(editor.plane()));
+            }
+        });
+    if !open {
+        editor.open = false;
+        mirror_state.set(MirrorState::NotPicking);
+    }
+}
+
+fn pick_mirror_plane(
+    mut editor: ResMut<MirrorEditor>,
+    mut mirror_state: ResMut<NextState<MirrorState>>,
+    cursor_ray: Res<CursorRay>,
+    mut raycast: Raycast,
+    grid_query: Query<(), With<InfiniteGrid>>,
+    mouse: Res<ButtonInput<MouseButton>>,
+) {
+    if mouse.just_pressed(MouseButton::Right) {
+        mirror_state.set(MirrorState::NotPicking);
+        return;
+    }
+    if !mouse.just_pressed(MouseButton::Left) {
+        return;
+    }
+    let Some(cursor_ray) = **cursor_ray else {
+        return;
+    };
+    let intersection_array = raycast.cast_ray(
+        cursor_ray,
+        &RaycastSettings {
+            filter: &|filter_entity| !grid_query.contains(filter_entity),
+            ..default()
+        },
+    );
+    if let Some((_, intersection)) = intersection_array.first() {
+        editor.choice = PlaneChoice::Picked(MirrorPlane {
+            point: intersection.position(),
+            normal: intersection.normal(),
+        });
+        mirror_state.set(MirrorState::NotPicking);
+    }
+}
+
+fn draw_mirror_plane(editor: Res<MirrorEditor>, mut gizmos: Gizmos) {
+    if !editor.open {
+        return;
+    }
+    let plane = editor.plane();
+    gizmos.rect(
+        plane.point,
+        Quat::from_rotation_arc(Vec3::Z, plane.normal.normalize()),
+        Vec2::splat(10.0),
+        Color::rgba(0.0, 0.58, 1.0, 0.8),
+    );
+}
@@ -1,12 +1,20 @@
+mod pivot;
+use pivot::PivotMode;
+mod snapping;
+pub use snapping::snap_indicator_text;
 mod ui;
-
-use core::f32::consts::PI;
+pub use ui::NumericEntry;
 
 use crate::{
     actions::{Action, ActionList},
+    assembly::{top_level_assembly, Assembly},
+    bounds::{world_bounds, BoundsQuery},
     constraints::ConstrainState,
-    placing::{CurrentlyPlacing, Part, PlacingState},
-    settings::Settings,
+    keybinds::{ActionState, InputAction, Modifiers},
+    measure::MeasureState,
+    mirror::MirrorState,
+    placing::{find_part_root, CurrentlyPlacing, Part, PartName, PlacingState},
+    settings::{Settings, SnapMode},
     ui::editor::Models,
 };
 use bevy::{
@@ -33,12 +41,22 @@ impl Plugin for MoveObjectsPlugin {
     fn build(&self, app: &mut App) {
         app.add_plugins(EguiPlugin)
             .init_state::<MoveObjectsState>()
+            .init_resource::<ui::NumericEntry>()
+            .init_resource::<snapping::SnapTargets>()
+            .init_resource::<pivot::Cursor3d>()
+            .init_resource::<pivot::PivotHole>()
             .add_systems(Startup, setup)
             .add_systems(
                 Update,
                 (
-                    update,
-                    select_object.run_if(not(in_state(PlacingState::Placing))),
+                    (snapping::gather_snap_targets, update, ui::numeric_entry).chain(),
+                    snapping::cycle_snap_mode,
+                    (pivot::pick_pivot, pivot::draw_pivot),
+                    select_object.run_if(
+                        not(in_state(PlacingState::Placing))
+                            .and_then(not(in_state(MirrorState::PickingPlane)))
+                            .and_then(not(in_state(MeasureState::Measuring))),
+                    ),
                     (unselect_object, ui::change_gizmo_mode, delete_object)
                         .run_if(in_state(MoveObjectsState::Moving)),
                 )
@@ -56,6 +74,7 @@ struct GizmoOptions {
     last_result: Option<GizmoResult>,
     custom_highlight_color: bool,
     visuals: GizmoVisuals,
+    pivot: PivotMode,
 }
 
 fn setup(mut commands: Commands, settings: Res<Settings>) {
@@ -63,10 +82,11 @@ fn setup(mut commands: Commands, settings: Res<Settings>) {
         gizmo_mode: GizmoMode::Translate,
         gizmo_orientation: GizmoOrientation::Global,
         precision_snap: settings.precision_snap,
-        snap_angle: PI / 12.0, // 15 degrees
-        snap_distance: 0.20,
+        snap_angle: settings.snap_angle.to_radians(),
+        snap_distance: settings.snap_distance,
         last_result: None,
         custom_highlight_color: false,
+        pivot: PivotMode::Origin,
         visuals: GizmoVisuals {
             x_color: Color32::from_rgb(255, 0, 148),
             y_color: Color32::from_rgb(148, 255, 0),
@@ -82,22 +102,74 @@ fn setup(mut commands: Commands, settings: Res<Settings>) {
 #[derive(Component)]
 pub struct CurrentlyMoving;
 
+/// Replaces the selection with the given parts, or the top level assemblies they're in
+pub fn select_parts(
+    commands: &mut Commands,
+    parts: impl IntoIterator<Item = Entity>,
+    selected_query: &Query<Entity, With<CurrentlyMoving>>,
+    parent_query: &Query<&Parent>,
+    assembly_query: &Query<(), With<Assembly>>,
+    moving_state: &mut NextState<MoveObjectsState>,
+) {
+    for entity in selected_query.iter() {
+        commands.entity(entity).remove::<CurrentlyMoving>();
+    }
+    for part in parts {
+        let target =
+            top_level_assembly(part, parent_query, |entity| assembly_query.contains(entity));
+        commands.entity(target).insert(CurrentlyMoving);
+    }
+    moving_state.set(MoveObjectsState::Moving);
+}
+
+/// Run condition for while the selection is being dragged with the gizmo
+pub fn gizmo_dragging(numeric_entry: Res<ui::NumericEntry>) -> bool {
+    numeric_entry.dragging
+}
+
+/// Locked parts can't be picked in the viewport, moved or deleted
+#[derive(Component)]
+pub struct Locked;
+
+/// Selected parts that the gizmo can move
+type TargetQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Entity,
+        &'static mut Transform,
+        &'static GlobalTransform,
+        Option<&'static Parent>,
+    ),
+    (With<CurrentlyMoving>, Without<Locked>),
+>;
+
 fn update(
     mut contexts: EguiContexts,
-    camera_q: Query<(&Camera, &Transform), (Without<CurrentlyMoving>, Without<Camera2d>)>,
-    mut target_q: Query<(Entity, &mut Transform), With<CurrentlyMoving>>,
+    camera_q: Query<
+        (&Camera, &Transform, &Projection),
+        (Without<CurrentlyMoving>, Without<Camera2d>),
+    >,
+    mut target_q: TargetQuery,
+    global_transform_q: Query<&GlobalTransform>,
     mut gizmo_options: ResMut<GizmoOptions>,
     constrain_state: Res<State<ConstrainState>>,
     window: Query<&Window>,
     mut action_list: ResMut<ActionList>,
+    mut numeric_entry: ResMut<ui::NumericEntry>,
+    settings: Res<Settings>,
+    snap_targets: Res<snapping::SnapTargets>,
+    (pivot_hole, cursor_3d): (Res<pivot::PivotHole>, Res<pivot::Cursor3d>),
+    children_q: Query<&Children>,
+    aabb_q: BoundsQuery,
 ) {
     if *constrain_state == ConstrainState::Constraining {
         return;
     }
     let (projection_matrix, view_matrix) = {
-        let (camera, transform) = camera_q.single();
+        let (camera, transform, projection) = camera_q.single();
         (
-            camera.projection_matrix(),
+            crate::movement::projection_matrix(camera, projection),
             transform.compute_matrix().inverse(),
         )
     };
@@ -111,6 +183,8 @@ fn update(
         .show(contexts.ctx_mut(), |ui| {
             ui.with_layer_id(LayerId::background(), |ui| {
                 let precise_snap = gizmo_options.precision_snap;
+                // Snapping to other parts is done below, instead of in steps by the gizmo
+                let snap_to_geometry = settings.snap_mode == SnapMode::Geometry;
 
                 // Snap angle to use for rotation when snapping is enabled.
                 let snap_angle = if precise_snap {
@@ -135,7 +209,30 @@ fn update(
                     ..gizmo_options.visuals
                 };
 
-                let model_matrix = target_q.single_mut().1.clone().compute_matrix();
+                // The gizmo sits on the pivot, turned like the first selected part so that local
+                // orientation follows that part
+                let first_transform = *target_q.iter().next().unwrap().2;
+                let pivot_point = match gizmo_options.pivot {
+                    PivotMode::Origin => None,
+                    PivotMode::BoundsCenter => target_q
+                        .iter()
+                        .filter_map(|(entity, _, _, _)| world_bounds(entity, &children_q, &aabb_q))
+                        .reduce(|(a_min, a_max), (b_min, b_max)| {
+                            (a_min.min(b_min), a_max.max(b_max))
+                        })
+                        .map(|(min, max)| (min + max) / 2.0),
+                    PivotMode::Hole => pivot_hole
+                        .0
+                        .and_then(|hole| global_transform_q.get(hole).ok())
+                        .map(|hole_transform| hole_transform.translation()),
+                    PivotMode::Cursor => cursor_3d.0,
+                }
+                .unwrap_or(first_transform.translation());
+                let (_, first_rotation, _) = first_transform.to_scale_rotation_translation();
+                let pivot_transform = GlobalTransform::from(
+                    Transform::from_translation(pivot_point).with_rotation(first_rotation),
+                );
+                let model_matrix = pivot_transform.compute_matrix();
 
                 let gizmo = Gizmo::new("Move Objects Gizmo")
                     .view_matrix(view_matrix.to_cols_array_2d().into())
@@ -143,30 +240,72 @@ fn update(
                     .model_matrix(model_matrix.to_cols_array_2d().into())
                     .mode(gizmo_options.gizmo_mode)
                     .orientation(gizmo_options.gizmo_orientation)
-                    .snapping(precise_snap)
+                    .snapping(precise_snap && !snap_to_geometry)
                     .snap_angle(snap_angle)
                     .snap_distance(snap_distance)
                     .visuals(visuals);
 
                 gizmo_options.last_result = gizmo.interact(ui);
 
+                if gizmo_options.last_result.is_none() && numeric_entry.dragging {
+                    numeric_entry.finish_drag(&target_q, &mut action_list);
+                }
+
                 if let Some(gizmo_response) = gizmo_options.last_result {
-                    let (target_entity, mut target_transform) = target_q.single_mut();
-                    let previous_transform = target_transform.clone();
+                    if !numeric_entry.dragging {
+                        numeric_entry.start_drag(
+                            gizmo_response.mode,
+                            pivot_point,
+                            target_q
+                                .iter()
+                                .map(|(entity, transform, global_transform, _)| {
+                                    (entity, *transform, *global_transform)
+                                })
+                                .collect(),
+                        );
+                    }
+                    if let Some(value) = gizmo_response.value {
+                        numeric_entry.axis = Vec3::from(value).try_normalize();
+                    }
 
                     // We have to do some manual translation because of a new update in the
                     // egui-gizmo dependency.
-                    target_transform.translation = Vec3::new(
-                        gizmo_response.translation.x,
-                        gizmo_response.translation.y,
-                        gizmo_response.translation.z,
-                    );
-                    target_transform.rotation = Quat::from_array(*gizmo_response.rotation.as_ref());
-                    target_transform.scale = Vec3::new(
-                        gizmo_response.scale.x,
-                        gizmo_response.scale.y,
-                        gizmo_response.scale.z,
-                    );
+                    let mut new_pivot_transform = Transform {
+                        translation: Vec3::new(
+                            gizmo_response.translation.x,
+                            gizmo_response.translation.y,
+                            gizmo_response.translation.z,
+                        ),
+                        rotation: Quat::from_array(*gizmo_response.rotation.as_ref()),
+                        scale: Vec3::new(
+                            gizmo_response.scale.x,
+                            gizmo_response.scale.y,
+                            gizmo_response.scale.z,
+                        ),
+                    };
+                    if precise_snap
+                        && snap_to_geometry
+                        && gizmo_response.mode == GizmoMode::Translate
+                    {
+                        let translation = pivot_transform.translation();
+                        new_pivot_transform.translation = translation
+                            + snap_targets.snap(
+                                new_pivot_transform.translation - translation,
+                                settings.snap_distance,
+                            );
+                    }
+                    let delta = new_pivot_transform.compute_matrix() * model_matrix.inverse();
+
+                    for (_, mut target_transform, global_transform, parent) in target_q.iter_mut() {
+                        let new_global_matrix = delta * global_transform.compute_matrix();
+                        // Parts inside an assembly are positioned relative to the assembly
+                        let parent_matrix = parent
+                            .and_then(|parent| global_transform_q.get(parent.get()).ok())
+                            .map(|parent_transform| parent_transform.compute_matrix())
+                            .unwrap_or(Mat4::IDENTITY);
+                        *target_transform =
+                            Transform::from_matrix(parent_matrix.inverse() * new_global_matrix);
+                    }
 
                     let window = window.get_single().unwrap();
 
@@ -175,14 +314,10 @@ fn update(
                         window.resolution.physical_height() as f32,
                     );
 
-                    ui::show_gizmo_status(ui, gizmo_response, window_size);
-
-                    action_list.0.push(Action::Moved(
-                        target_entity,
-                        previous_transform.clone(),
-                        target_transform.clone(),
-                    ));
+                    ui::show_gizmo_status(ui, gizmo_response, window_size, settings.unit);
                 }
+
+                ui::show_gizmo_settings(ui, gizmo_options.gizmo_orientation, gizmo_options.pivot);
             });
         });
 }
@@ -199,13 +334,22 @@ fn select_object(
     constrain_state: Res<State<ConstrainState>>,
     models: Res<Models>,
     mesh_query: Query<&Handle<Mesh>>,
-    asset_server_gltf: Res<Assets<Gltf>>,
-    asset_server_gltf_mesh: Res<Assets<GltfMesh>>,
+    (asset_server_gltf, asset_server_gltf_mesh): (Res<Assets<Gltf>>, Res<Assets<GltfMesh>>),
+    parent_query: Query<&Parent>,
+    // (is a part, is an assembly, is locked)
+    kind_query: Query<(Has<PartName>, Has<Assembly>, Has<Locked>)>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    mut contexts: EguiContexts,
 ) {
     if !mouse_buttons.just_pressed(MouseButton::Left) {
         return;
     }
 
+    // Clicks on a panel (like the parts list) shouldn't go through to the parts behind it
+    if contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+
     if placed_query.is_empty() {
         return;
     }
@@ -235,8 +379,12 @@ fn select_object(
         return;
     }
 
-    for entity in target_query.iter_mut() {
-        commands.entity(entity).remove::<CurrentlyMoving>();
+    // Holding control adds to (or removes from) the current selection
+    let add_to_selection = Modifiers::pressed(&keyboard).ctrl;
+    if !add_to_selection {
+        for entity in target_query.iter_mut() {
+            commands.entity(entity).remove::<CurrentlyMoving>();
+        }
     }
     let mut collision_entity: Option<Entity> = None;
     for (entity, _) in intersection_array.iter() {
@@ -276,7 +424,37 @@ fn select_object(
     }
     let collision_entity = collision_entity.unwrap();
 
-    commands.entity(collision_entity).insert(CurrentlyMoving);
+    let Some(part_entity) = find_part_root(collision_entity, &parent_query, |entity| {
+        kind_query.get(entity).is_ok_and(|(part, _, _)| part)
+    }) else {
+        return;
+    };
+    // Clicking a part inside an assembly selects the whole assembly, unless alt is held
+    let part_entity = if Modifiers::pressed(&keyboard).alt {
+        part_entity
+    } else {
+        top_level_assembly(part_entity, &parent_query, |entity| {
+            kind_query
+                .get(entity)
+                .is_ok_and(|(_, assembly, _)| assembly)
+        })
+    };
+    if kind_query
+        .get(part_entity)
+        .is_ok_and(|(_, _, locked)| locked)
+    {
+        return;
+    }
+
+    if add_to_selection && target_query.contains(part_entity) {
+        commands.entity(part_entity).remove::<CurrentlyMoving>();
+        if target_query.iter().count() == 1 {
+            moving_state.set(MoveObjectsState::NotMoving);
+        }
+        return;
+    }
+
+    commands.entity(part_entity).insert(CurrentlyMoving);
     moving_state.set(MoveObjectsState::Moving);
 }
 
@@ -284,37 +462,42 @@ fn unselect_object(
     mut commands: Commands,
     mut target_query: Query<Entity, With<CurrentlyMoving>>,
     mut moving_state: ResMut<NextState<MoveObjectsState>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
 ) {
-    if !keyboard.just_pressed(KeyCode::Escape) {
+    if !actions.just_pressed(InputAction::Cancel) {
         return;
     }
     moving_state.set(MoveObjectsState::NotMoving);
-    if let Some(entity) = target_query.get_single_mut().ok() {
+    for entity in target_query.iter_mut() {
         commands.entity(entity).remove::<CurrentlyMoving>();
     }
 }
 
 fn delete_object(
     mut commands: Commands,
-    target_query: Query<(Entity, &Name, &Transform), With<CurrentlyMoving>>,
+    target_query: Query<
+        (Entity, AnyOf<(&PartName, &Assembly)>, &Transform),
+        (With<CurrentlyMoving>, Without<Locked>),
+    >,
     mut moving_state: ResMut<NextState<MoveObjectsState>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut action_list: ResMut<ActionList>,
 ) {
-    if !keyboard.just_pressed(KeyCode::KeyX) {
+    if !actions.just_pressed(InputAction::Delete) {
         return;
     }
     moving_state.set(MoveObjectsState::NotMoving);
-    if let Some(target) = target_query.get_single().ok() {
+    for target in target_query.iter() {
         let entity = target.0;
-        let part_name = target.1;
+        let part_name = match target.1 {
+            (Some(part_name), _) => part_name.0.clone(),
+            (None, Some(assembly)) => assembly.name.clone(),
+            (None, None) => unreachable!(),
+        };
         let transform = target.2;
-        action_list.0.push(Action::Deleted(
-            entity,
-            part_name.to_string().clone(),
-            transform.clone(),
-        ));
+        action_list
+            .0
+            .push(Action::Deleted(entity, part_name, transform.clone()));
         commands.entity(entity).despawn_recursive();
     }
 }
@@ -0,0 +1,142 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_infinite_grid::InfiniteGrid;
+use bevy_mod_raycast::prelude::*;
+
+use crate::{
+    constraints::ConstrainComponent,
+    keybinds::Modifiers,
+    placing::{find_part_root, PartName},
+};
+
+use super::GizmoOptions;
+
+/// Point the gizmo moves and rotates the selection around
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum PivotMode {
+    /// Origin of the first selected part
+    #[default]
+    Origin,
+    /// Center of the bounding box around everything selected
+    BoundsCenter,
+    /// A constraint hole picked with Alt + Right Click
+    Hole,
+    /// The 3D cursor, placed with Shift + Right Click
+    Cursor,
+}
+
+impl PivotMode {
+    pub const ALL: [PivotMode; 4] = [Self::Origin, Self::BoundsCenter, Self::Hole, Self::Cursor];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Origin => "Part origin",
+            Self::BoundsCenter => "Selection center",
+            Self::Hole => "Picked hole",
+            Self::Cursor => "3D cursor",
+        }
+    }
+}
+
+/// A point in the world that can be used as a pivot
+#[derive(Resource, Default, Debug)]
+pub struct Cursor3d(pub Option<Vec3>);
+
+/// Constraint marker of the hole used as a pivot, so the pivot moves along with its part
+#[derive(Resource, Default, Debug)]
+pub struct PivotHole(pub Option<Entity>);
+
+/// Shift + Right Click places the 3D cursor, Alt + Right Click picks the nearest hole as the pivot
+pub fn pick_pivot(
+    mut contexts: EguiContexts,
+    mouse: Res<ButtonInput<MouseButton>>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+    cursor_ray: Res<CursorRay>,
+    mut raycast: Raycast,
+    grid_query: Query<(), With<InfiniteGrid>>,
+    part_query: Query<(), With<PartName>>,
+    parent_query: Query<&Parent>,
+    hole_query: Query<(Entity, &GlobalTransform, &Parent), With<ConstrainComponent>>,
+    mut cursor_3d: ResMut<Cursor3d>,
+    mut pivot_hole: ResMut<PivotHole>,
+    mut gizmo_options: ResMut<GizmoOptions>,
+) {
+    let modifiers = Modifiers::pressed(&keyboard);
+    let placing_cursor = modifiers.shift;
+    let picking_hole = modifiers.alt;
+    if !mouse.just_pressed(MouseButton::Right) || !(placing_cursor || picking_hole) {
+        return;
+    }
+    if contexts.ctx_mut().is_pointer_over_area() {
+        return;
+    }
+    let Some(cursor_ray) = **cursor_ray else {
+        return;
+    };
+
+    if placing_cursor {
+        // The ground counts too, the cursor is often wanted on the floor
+        if let Some((_, intersection)) = raycast
+            .cast_ray(cursor_ray, &RaycastSettings::default())
+            .first()
+        {
+            cursor_3d.0 = Some(intersection.position());
+            gizmo_options.pivot = PivotMode::Cursor;
+        }
+        return;
+    }
+
+    let Some((hit_entity, intersection)) = raycast
+        .cast_ray(
+            cursor_ray,
+            &RaycastSettings {
+                filter: &|filter_entity| !grid_query.contains(filter_entity),
+                ..default()
+            },
+        )
+        .first()
+        .map(|(entity, intersection)| (*entity, intersection.position()))
+    else {
+        return;
+    };
+    let Some(part) = find_part_root(hit_entity, &parent_query, |entity| {
+        part_query.contains(entity)
+    }) else {
+        return;
+    };
+    let closest_hole = hole_query
+        .iter()
+        .filter(|(_, _, parent)| parent.get() == part)
+        .min_by(|(_, a, _), (_, b, _)| {
+            a.translation()
+                .distance_squared(intersection)
+                .total_cmp(&b.translation().distance_squared(intersection))
+        });
+    if let Some((hole, _, _)) = closest_hole {
+        pivot_hole.0 = Some(hole);
+        gizmo_options.pivot = PivotMode::Hole;
+    }
+}
+
+pub fn draw_pivot(
+    cursor_3d: Res<Cursor3d>,
+    pivot_hole: Res<PivotHole>,
+    gizmo_options: Res<GizmoOptions>,
+    global_transform_query: Query<&GlobalTransform>,
+    mut gizmos: Gizmos,
+) {
+    if let Some(cursor) = cursor_3d.0 {
+        gizmos.sphere(cursor, Quat::IDENTITY, 0.15, Color::WHITE);
+        for axis in [Vec3::X, Vec3::Y, Vec3::Z] {
+            gizmos.line(cursor - axis * 0.3, cursor + axis * 0.3, Color::WHITE);
+        }
+    }
+    if gizmo_options.pivot == PivotMode::Hole {
+        if let Some(hole) = pivot_hole
+            .0
+            .and_then(|hole| global_transform_query.get(hole).ok())
+        {
+            gizmos.sphere(hole.translation(), Quat::IDENTITY, 0.12, Color::YELLOW);
+        }
+    }
+}
@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::{
+    bounds::{world_bounds, BoundsQuery},
+    constraints::ConstrainComponent,
+    keybinds::{ActionState, InputAction},
+    placing::{CurrentlyPlacing, PartName},
+    settings::{Settings, SnapMode},
+    units::HOLE_PITCH,
+};
+
+use super::CurrentlyMoving;
+
+/// Holes and bounding box faces of the parts that aren't selected, along with the selection's own
+/// holes and bounds, so a drag can line them up. Only gathered while snapping to other parts
+#[derive(Resource, Default)]
+pub struct SnapTargets {
+    selection_points: Vec<Vec3>,
+    selection_bounds: Option<(Vec3, Vec3)>,
+    points: Vec<Vec3>,
+    /// Coordinates of the faces along each axis
+    faces: [Vec<f32>; 3],
+}
+
+impl SnapTargets {
+    /// Adjusts how far the selection is being moved so that it lines up with another part
+    pub fn snap(&self, delta: Vec3, threshold: f32) -> Vec3 {
+        // Holes lining up are more useful than faces lining up
+        let mut closest_point: Option<(f32, Vec3)> = None;
+        for point in self.selection_points.iter() {
+            let moved = *point + delta;
+            for target in self.points.iter() {
+                let distance = moved.distance(*target);
+                if distance < threshold
+                    && closest_point.map_or(true, |(closest, _)| distance < closest)
+                {
+                    closest_point = Some((distance, *target - *point));
+                }
+            }
+        }
+        if let Some((_, snapped)) = closest_point {
+            return snapped;
+        }
+
+        let Some((min, max)) = self.selection_bounds else {
+            return delta;
+        };
+        let mut snapped = delta;
+        for axis in 0..3 {
+            let mut closest_correction: Option<f32> = None;
+            for face in self.faces[axis].iter() {
+                for side in [min[axis], max[axis]] {
+                    let correction = face - (side + delta[axis]);
+                    if correction.abs() < threshold
+                        && closest_correction
+                            .map_or(true, |closest| correction.abs() < closest.abs())
+                    {
+                        closest_correction = Some(correction);
+                    }
+                }
+            }
+            snapped[axis] += closest_correction.unwrap_or(0.0);
+        }
+        snapped
+    }
+}
+
+fn is_selected(
+    entity: Entity,
+    parent_query: &Query<&Parent>,
+    selected_query: &Query<(), With<CurrentlyMoving>>,
+) -> bool {
+    std::iter::once(entity)
+        .chain(parent_query.iter_ancestors(entity))
+        .any(|ancestor| selected_query.contains(ancestor))
+}
+
+pub fn gather_snap_targets(
+    mut snap_targets: ResMut<SnapTargets>,
+    settings: Res<Settings>,
+    selected_query: Query<(), With<CurrentlyMoving>>,
+    part_query: Query<Entity, (With<PartName>, Without<CurrentlyPlacing>)>,
+    constraint_query: Query<(Entity, &GlobalTransform), With<ConstrainComponent>>,
+    parent_query: Query<&Parent>,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+) {
+    *snap_targets = SnapTargets::default();
+    if settings.snap_mode != SnapMode::Geometry || selected_query.is_empty() {
+        return;
+    }
+
+    for (entity, global_transform) in constraint_query.iter() {
+        if is_selected(entity, &parent_query, &selected_query) {
+            snap_targets
+                .selection_points
+                .push(global_transform.translation());
+        } else {
+            snap_targets.points.push(global_transform.translation());
+        }
+    }
+
+    for part in part_query.iter() {
+        let Some((min, max)) = world_bounds(part, &children_query, &aabb_query) else {
+            continue;
+        };
+        if is_selected(part, &parent_query, &selected_query) {
+            snap_targets.selection_bounds = Some(match snap_targets.selection_bounds {
+                Some((selection_min, selection_max)) => {
+                    (selection_min.min(min), selection_max.max(max))
+                }
+                None => (min, max),
+            });
+        } else {
+            for axis in 0..3 {
+                snap_targets.faces[axis].push(min[axis]);
+                snap_targets.faces[axis].push(max[axis]);
+            }
+        }
+    }
+}
+
+/// Switches between snapping to the grid, to the hole pitch and to other parts
+pub fn cycle_snap_mode(actions: Res<ActionState>, mut settings: ResMut<Settings>) {
+    if !actions.just_pressed(InputAction::CycleSnapMode) {
+        return;
+    }
+    let index = SnapMode::ALL
+        .iter()
+        .position(|mode| *mode == settings.snap_mode)
+        .unwrap_or(0);
+    settings.snap_mode = SnapMode::ALL[(index + 1) % SnapMode::ALL.len()];
+    settings.write_changes();
+}
+
+/// Text for the top bar, like "Snap: Grid 0.20in / 15°"
+pub fn snap_indicator_text(settings: &Settings, snapping: bool) -> String {
+    if !snapping {
+        return "Snap: Off".to_string();
+    }
+    let distance = match settings.snap_mode {
+        SnapMode::Grid => settings.unit.format(settings.snap_distance),
+        SnapMode::HolePitch => settings.unit.format(HOLE_PITCH),
+        SnapMode::Geometry => "holes & faces".to_string(),
+    };
+    format!(
+        "Snap: {} {} / {}°",
+        settings.snap_mode.label(),
+        distance,
+        settings.snap_angle
+    )
+}
@@ -1,23 +1,56 @@
-use crate::{move_objects::GizmoOptions, move_objects::GizmoResult};
+use crate::{
+    actions::{Action, ActionList},
+    keybinds::{ActionState, InputAction},
+    move_objects::GizmoOptions,
+    move_objects::GizmoResult,
+    settings::{Settings, SnapMode},
+    units::{LengthUnit, HOLE_PITCH},
+};
 use bevy::prelude::*;
-use egui_gizmo::GizmoMode;
+use bevy_egui::EguiContexts;
+use egui_gizmo::{GizmoMode, GizmoOrientation};
+
+use super::{PivotMode, TargetQuery};
 
 use core::f32::consts::TAU;
 use egui::{pos2, Align2, Color32, FontId, Ui};
 
 // TODO: Make this an actual UI
-pub fn change_gizmo_mode(keyboard: Res<ButtonInput<KeyCode>>, mut options: ResMut<GizmoOptions>) {
-    if keyboard.just_pressed(KeyCode::KeyT) {
+pub fn change_gizmo_mode(
+    actions: Res<ActionState>,
+    mut options: ResMut<GizmoOptions>,
+    settings: Res<Settings>,
+) {
+    if actions.just_pressed(InputAction::ToggleGizmoMode) {
         options.gizmo_mode = match options.gizmo_mode {
             GizmoMode::Rotate => GizmoMode::Translate,
             GizmoMode::Translate => GizmoMode::Rotate,
             _ => GizmoMode::Translate, // Erm what the freak
         }
     }
-    options.precision_snap = !keyboard.pressed(KeyCode::ShiftLeft);
+    if actions.just_pressed(InputAction::ToggleGizmoOrientation) {
+        options.gizmo_orientation = match options.gizmo_orientation {
+            GizmoOrientation::Global => GizmoOrientation::Local,
+            GizmoOrientation::Local => GizmoOrientation::Global,
+        }
+    }
+    if actions.just_pressed(InputAction::CyclePivot) {
+        let index = PivotMode::ALL
+            .iter()
+            .position(|pivot| *pivot == options.pivot)
+            .unwrap_or(0);
+        options.pivot = PivotMode::ALL[(index + 1) % PivotMode::ALL.len()];
+    }
+    options.precision_snap =
+        settings.precision_snap != actions.pressed(InputAction::DisableSnapping);
+    options.snap_angle = settings.snap_angle.to_radians();
+    options.snap_distance = match settings.snap_mode {
+        SnapMode::HolePitch => HOLE_PITCH,
+        _ => settings.snap_distance,
+    };
 }
 
-pub fn show_gizmo_status(ui: &Ui, response: GizmoResult, window_size: Vec2) {
+pub fn show_gizmo_status(ui: &Ui, response: GizmoResult, window_size: Vec2, unit: LengthUnit) {
     let value = response.value;
     if value.is_none() {
         return;
@@ -32,8 +65,10 @@ pub fn show_gizmo_status(ui: &Ui, response: GizmoResult, window_size: Vec2) {
         GizmoMode::Rotate => format!("{:.1}°, {:.2} rad", degrees % 360.0, length % TAU,),
 
         GizmoMode::Translate => format!(
-            "dX: {:.2}, dY: {:.2}, dZ: {:.2}",
-            value[0], value[1], value[2]
+            "dX: {}, dY: {}, dZ: {}",
+            unit.format(value[0]),
+            unit.format(value[1]),
+            unit.format(value[2])
         ),
 
         GizmoMode::Scale => format!("Scale mode might not work as intended!"),
@@ -52,3 +87,253 @@ pub fn show_gizmo_status(ui: &Ui, response: GizmoResult, window_size: Vec2) {
         Color32::WHITE,
     );
 }
+
+/// Shows how the gizmo is oriented and what it pivots around in the corner of the viewport
+pub fn show_gizmo_settings(ui: &Ui, orientation: GizmoOrientation, pivot: PivotMode) {
+    let orientation = match orientation {
+        GizmoOrientation::Global => "Global",
+        GizmoOrientation::Local => "Local",
+    };
+    let rect = ui.clip_rect();
+    ui.painter().text(
+        pos2(rect.left() + 10.0, rect.bottom() - 10.0),
+        Align2::LEFT_BOTTOM,
+        format!("{orientation} (,)  Pivot: {} (.)", pivot.label()),
+        FontId::proportional(14.0),
+        Color32::WHITE,
+    );
+}
+
+/// Lets an exact offset or angle be typed in during (or right after) a gizmo drag, like "x 2.5",
+/// "r 45" or just "2.5" to go that far in the direction that was dragged
+#[derive(Resource, Default)]
+pub struct NumericEntry {
+    pub(super) dragging: bool,
+    /// The box stays open after a drag until the value is applied or dismissed
+    open: bool,
+    just_released: bool,
+    mode: Option<GizmoMode>,
+    /// What rotations go around
+    pivot: Vec3,
+    /// Direction of the drag, or the axis of the rotation
+    pub(super) axis: Option<Vec3>,
+    text: String,
+    /// Local and world transforms of the selected parts from before the drag
+    start: Vec<(Entity, Transform, GlobalTransform)>,
+    /// The move that was put in the undo list for this drag
+    committed: Vec<(Entity, Transform, Transform)>,
+}
+
+enum TypedMove {
+    Translate(Vec3),
+    /// Axis and degrees
+    Rotate(Vec3, f32),
+}
+
+impl NumericEntry {
+    /// Whether the gizmo is being dragged, when anything typed goes into the box
+    pub fn dragging(&self) -> bool {
+        self.dragging
+    }
+
+    pub(super) fn start_drag(
+        &mut self,
+        mode: GizmoMode,
+        pivot: Vec3,
+        start: Vec<(Entity, Transform, GlobalTransform)>,
+    ) {
+        *self = Self {
+            dragging: true,
+            mode: Some(mode),
+            pivot,
+            start,
+            ..default()
+        };
+    }
+
+    pub(super) fn finish_drag(&mut self, target_q: &TargetQuery, action_list: &mut ActionList) {
+        self.dragging = false;
+        self.open = true;
+        self.just_released = true;
+        let moves = self.moves(target_q);
+        self.commit(moves, action_list);
+    }
+
+    fn moves(&self, target_q: &TargetQuery) -> Vec<(Entity, Transform, Transform)> {
+        self.start
+            .iter()
+            .filter_map(|(entity, start_transform, _)| {
+                let (_, transform, _, _) = target_q.get(*entity).ok()?;
+                (*transform != *start_transform).then(|| (*entity, *start_transform, *transform))
+            })
+            .collect()
+    }
+
+    /// Puts the move in the undo list, in place of the drag's move so the two are a single step
+    fn commit(&mut self, moves: Vec<(Entity, Transform, Transform)>, action_list: &mut ActionList) {
+        let previous = action_list.0.iter().rposition(|action| {
+            matches!(action, Action::Moved(previous) if !self.committed.is_empty() && *previous == self.committed)
+        });
+        match previous {
+            Some(index) if moves.is_empty() => {
+                action_list.0.remove(index);
+            }
+            Some(index) => action_list.0[index] = Action::Moved(moves.clone()),
+            None if moves.is_empty() => {}
+            None => action_list.0.push(Action::Moved(moves.clone())),
+        }
+        self.committed = moves;
+    }
+
+    /// Typed lengths are in the unit from the settings
+    fn parse(&self, unit: LengthUnit) -> Option<TypedMove> {
+        let text = self.text.trim().to_lowercase();
+        let split = text
+            .find(|character: char| !character.is_ascii_alphabetic())
+            .unwrap_or(text.len());
+        let (prefix, number) = text.split_at(split);
+        let value: f32 = number.trim().parse().ok()?;
+        let length = unit.to_inches(value);
+        let rotating = self.mode == Some(GizmoMode::Rotate);
+        Some(match prefix.trim() {
+            "x" => TypedMove::Translate(Vec3::X * length),
+            "y" => TypedMove::Translate(Vec3::Y * length),
+            "z" => TypedMove::Translate(Vec3::Z * length),
+            "r" if rotating => TypedMove::Rotate(self.axis?, value),
+            "r" => TypedMove::Rotate(Vec3::Y, value),
+            "rx" => TypedMove::Rotate(Vec3::X, value),
+            "ry" => TypedMove::Rotate(Vec3::Y, value),
+            "rz" => TypedMove::Rotate(Vec3::Z, value),
+            "" if rotating => TypedMove::Rotate(self.axis?, value),
+            "" => TypedMove::Translate(self.axis? * length),
+            _ => return None,
+        })
+    }
+
+    /// Moves every part from where it was before the drag, rotating around the gizmo's pivot
+    fn apply(
+        &self,
+        typed_move: TypedMove,
+        target_q: &mut TargetQuery,
+        global_transform_q: &Query<&GlobalTransform>,
+    ) {
+        for (entity, _, start_global_transform) in self.start.iter() {
+            let Ok((_, mut transform, _, parent)) = target_q.get_mut(*entity) else {
+                continue;
+            };
+            let mut new_global_transform = start_global_transform.compute_transform();
+            match typed_move {
+                TypedMove::Translate(offset) => new_global_transform.translation += offset,
+                TypedMove::Rotate(axis, degrees) => new_global_transform.rotate_around(
+                    self.pivot,
+                    Quat::from_axis_angle(axis, degrees.to_radians()),
+                ),
+            }
+            let parent_matrix = parent
+                .and_then(|parent| global_transform_q.get(parent.get()).ok())
+                .map(|parent_transform| parent_transform.compute_matrix())
+                .unwrap_or(Mat4::IDENTITY);
+            *transform = Transform::from_matrix(
+                parent_matrix.inverse() * new_global_transform.compute_matrix(),
+            );
+        }
+    }
+}
+
+pub fn numeric_entry(
+    mut contexts: EguiContexts,
+    mut entry: ResMut<NumericEntry>,
+    mut target_q: TargetQuery,
+    global_transform_q: Query<&GlobalTransform>,
+    actions: Res<ActionState>,
+    mut action_list: ResMut<ActionList>,
+    settings: Res<Settings>,
+) {
+    if !entry.dragging && !entry.open {
+        return;
+    }
+    // Selecting something else dismisses it
+    if !entry.dragging
+        && entry
+            .start
+            .iter()
+            .any(|(entity, _, _)| !target_q.contains(*entity))
+    {
+        entry.open = false;
+        return;
+    }
+
+    let ctx = contexts.ctx_mut();
+    if entry.dragging {
+        // The mouse is busy with the gizmo, so anything typed goes straight into the box
+        let typed: Vec<egui::Event> = ctx.input(|input| input.events.clone());
+        for event in typed {
+            match event {
+                egui::Event::Text(text) => entry.text.extend(text.chars().filter(|character| {
+                    character.is_ascii_alphanumeric() || ".- ".contains(*character)
+                })),
+                egui::Event::Key {
+                    key: egui::Key::Backspace,
+                    pressed: true,
+                    ..
+                } => {
+                    entry.text.pop();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    let released = std::mem::take(&mut entry.just_released);
+    let mut apply = released && !entry.text.is_empty();
+    // Escape while typing only leaves the box, like in any other text box
+    let mut close = actions.just_pressed(InputAction::Cancel);
+    let valid = entry.text.is_empty() || entry.parse(settings.unit).is_some();
+    egui::Area::new("Numeric Entry")
+        .anchor(Align2::RIGHT_BOTTOM, [-10.0, -60.0])
+        .show(ctx, |ui| {
+            egui::Frame::popup(ui.style()).show(ui, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label(if valid {
+                        "Exact value"
+                    } else {
+                        "Try x 2.5 or r 45"
+                    });
+                    let dragging = entry.dragging;
+                    let response = ui.add(
+                        egui::TextEdit::singleline(&mut entry.text)
+                            .hint_text("x 2.5, r 45")
+                            .desired_width(80.0)
+                            .interactive(!dragging),
+                    );
+                    if dragging {
+                        return;
+                    }
+                    if actions.just_pressed(InputAction::TypeValue) {
+                        response.request_focus();
+                    }
+                    if response.lost_focus()
+                        && ui.input(|input| input.key_pressed(egui::Key::Enter))
+                    {
+                        apply = true;
+                    }
+                    if !released && response.clicked_elsewhere() {
+                        close = true;
+                    }
+                });
+            });
+        });
+
+    if apply {
+        if let Some(typed_move) = entry.parse(settings.unit) {
+            entry.apply(typed_move, &mut target_q, &global_transform_q);
+            let moves = entry.moves(&target_q);
+            entry.commit(moves, &mut action_list);
+        }
+        close = true;
+    }
+    if close && !entry.dragging {
+        entry.open = false;
+        entry.text.clear();
+    }
+}
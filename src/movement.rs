@@ -1,73 +1,561 @@
-use bevy::input::mouse::MouseMotion;
+use bevy::input::mouse::{MouseScrollUnit, MouseWheel};
 use bevy::prelude::*;
+use bevy::render::camera::{CameraProjection, ScalingMode};
+use bevy_editor_cam::extensions::look_to::LookToTrigger;
+use bevy_editor_cam::input::DefaultInputPlugin;
 use bevy_editor_cam::prelude::*;
+use bevy_egui::EguiContexts;
+use bevy_mod_raycast::prelude::*;
 
+use crate::bounds::{world_bounds, BoundsQuery};
+use crate::keybinds::{ActionState, InputAction, Modifiers};
+use crate::move_objects::CurrentlyMoving;
+use crate::placing::PartName;
 use crate::settings::Settings;
+use crate::ui::UIState;
 
 pub struct MovementPlugin;
 
 impl Plugin for MovementPlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(DefaultEditorCamPlugins)
+        // The default inputs orbit and pan with the left and right buttons, which select and place
+        // parts here, so the control profiles below drive the camera instead
+        app.add_event::<FrameCamera>()
+            .add_plugins(
+                DefaultEditorCamPlugins
+                    .build()
+                    .disable::<DefaultInputPlugin>(),
+            )
             .add_plugins(bevy_mod_picking::DefaultPickingPlugins)
-            .add_systems(Update, (switch_projection, update_camera));
+            .add_systems(
+                Update,
+                (
+                    switch_projection,
+                    (
+                        switch_control_state,
+                        update_camera,
+                        standard_view_keys,
+                        frame_keys,
+                    )
+                        .run_if(in_state(UIState::Editor)),
+                    (frame_camera, fly_camera).chain(),
+                    apply_camera_settings.run_if(resource_changed::<Settings>),
+                ),
+            );
     }
 }
 
-/*
-fn switch_control_state(
-    mut query: Query<&mut OrbitCameraController>,
-    keyboard: Res<ButtonInput<KeyCode>>,
-    mut settings: ResMut<Settings>,
-) {
-    if !keyboard.just_pressed(KeyCode::KeyP) {
-        return;
+/// How the camera is moved, saved as `Settings.control_state`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ControlProfile {
+    /// Shift + Middle Mouse to orbit, Middle Mouse to pan, scroll to zoom
+    #[default]
+    Mouse,
+    /// Hold Shift to orbit, hold Ctrl to pan, scroll with two fingers to zoom
+    Trackpad,
+    /// Barrel button to orbit, second barrel button to pan, Ctrl + barrel button to zoom
+    Pen,
+}
+
+impl ControlProfile {
+    pub const ALL: [ControlProfile; 3] = [Self::Mouse, Self::Trackpad, Self::Pen];
+
+    pub fn from_index(index: u8) -> Self {
+        Self::ALL.get(index as usize).copied().unwrap_or_default()
+    }
+
+    pub fn index(&self) -> u8 {
+        Self::ALL
+            .iter()
+            .position(|profile| profile == self)
+            .unwrap_or(0) as u8
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Mouse => "Mouse (PC)",
+            Self::Trackpad => "Trackpad (laptop)",
+            Self::Pen => "Pen",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Self::Mouse => "Shift + Middle Mouse to orbit, Middle Mouse to pan, scroll to zoom",
+            Self::Trackpad => "Hold Shift to orbit, hold Ctrl to pan, scroll to zoom",
+            Self::Pen => {
+                "Barrel button to orbit, second barrel button to pan, Ctrl + barrel button to zoom"
+            }
+        }
+    }
+
+    /// The camera move the held buttons and keys ask for, leaving out scrolling
+    fn motion(
+        &self,
+        mouse: &ButtonInput<MouseButton>,
+        modifiers: Modifiers,
+    ) -> Option<CameraMotion> {
+        match self {
+            Self::Mouse => mouse
+                .pressed(MouseButton::Middle)
+                .then_some(if modifiers.shift {
+                    CameraMotion::Orbit
+                } else {
+                    CameraMotion::Pan
+                }),
+            // Nothing is held while clicking, so Ctrl + Click still adds to the selection and
+            // Shift still turns off snapping during a gizmo drag
+            Self::Trackpad if mouse.pressed(MouseButton::Left) => None,
+            Self::Trackpad if modifiers.shift => Some(CameraMotion::Orbit),
+            Self::Trackpad if modifiers.ctrl => Some(CameraMotion::Pan),
+            Self::Trackpad => None,
+            // Shift and Alt + Right Click pick the pivot
+            Self::Pen
+                if mouse.pressed(MouseButton::Right) && !modifiers.shift && !modifiers.alt =>
+            {
+                Some(if modifiers.ctrl {
+                    CameraMotion::Zoom
+                } else {
+                    CameraMotion::Orbit
+                })
+            }
+            Self::Pen => mouse
+                .pressed(MouseButton::Middle)
+                .then_some(CameraMotion::Pan),
+        }
     }
-    if let Ok(mut controller) = query.get_single_mut() {
-        controller.toggle_control_state();
-        settings.set_control_state(controller.control_state);
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CameraMotion {
+    Orbit,
+    Pan,
+    Zoom,
+}
+
+fn switch_control_state(actions: Res<ActionState>, mut settings: ResMut<Settings>) {
+    if !actions.just_pressed(InputAction::ToggleControlMode) {
+        return;
     }
+    let profile = ControlProfile::from_index(settings.control_state);
+    let next = ControlProfile::ALL[(profile.index() as usize + 1) % ControlProfile::ALL.len()];
+    settings.control_state = next.index();
+    settings.write_changes();
 }
-*/
 
+/// Moves the camera according to the selected control profile. Every move is anchored to what's
+/// under the cursor, so orbiting goes around it and panning keeps it under the cursor
 fn update_camera(
-    mut editor_camera: Query<&mut EditorCam>,
+    mut contexts: EguiContexts,
+    mut commands: Commands,
+    mut camera_query: Query<(
+        Entity,
+        &mut EditorCam,
+        &GlobalTransform,
+        &mut Transform,
+        &mut Projection,
+    )>,
+    window_query: Query<&Window>,
     keyboard: Res<ButtonInput<KeyCode>>,
-    mut mouse_events: EventReader<MouseMotion>,
+    mouse: Res<ButtonInput<MouseButton>>,
+    mut mouse_wheel: EventReader<MouseWheel>,
+    cursor_ray: Res<CursorRay>,
+    mut raycast: Raycast,
     settings: Res<Settings>,
+    mut current_motion: Local<Option<CameraMotion>>,
+    mut last_cursor: Local<Option<Vec2>>,
 ) {
-    let mut editor_camera = editor_camera.get_single_mut().unwrap();
-    let view_space_anchor = editor_camera.anchor_view_space();
-    if keyboard.pressed(KeyCode::ShiftLeft) {
-        // editor_camera.enabled_motion.pan = true;
-        editor_camera.start_pan(view_space_anchor);
-        for event in mouse_events.read() {
-            let mut movement = event.delta * 100.0;
-            movement.x *= settings.translate_sensitivity.x;
-            movement.y *= settings.translate_sensitivity.y;
-
-            // editor_camera.send_screenspace_input(movement);
+    let Ok((camera, mut editor_camera, camera_transform, mut transform, mut projection)) =
+        camera_query.get_single_mut()
+    else {
+        return;
+    };
+    let window = window_query.get_single().ok();
+    let cursor = window.and_then(|window| window.cursor_position());
+    // -1 to 1 from the bottom left to the top right of the window
+    let cursor_ndc = cursor
+        .zip(window)
+        .map(|(cursor, window)| {
+            Vec2::new(
+                cursor.x / window.width() * 2.0 - 1.0,
+                1.0 - cursor.y / window.height() * 2.0,
+            )
+        })
+        .unwrap_or(Vec2::ZERO);
+    let cursor_delta = match (cursor, *last_cursor) {
+        (Some(cursor), Some(last)) => cursor - last,
+        _ => Vec2::ZERO,
+    };
+    *last_cursor = cursor;
+
+    let scroll: f32 = mouse_wheel
+        .read()
+        .map(|event| match event.unit {
+            MouseScrollUnit::Line => event.y,
+            MouseScrollUnit::Pixel => event.y * 0.01,
+        })
+        .sum();
+
+    let ctx = contexts.ctx_mut();
+    let orthographic = matches!(*projection, Projection::Orthographic(_));
+    let profile = ControlProfile::from_index(settings.control_state);
+    let wanted_motion = if ctx.is_using_pointer() {
+        None
+    } else {
+        profile
+            .motion(&mouse, Modifiers::pressed(&keyboard))
+            .or((scroll != 0.0).then_some(CameraMotion::Zoom))
+    };
+
+    if wanted_motion != *current_motion {
+        if current_motion.is_some() {
+            editor_camera.end_move();
+            *current_motion = None;
+        }
+        // Moves can't start over a window, but one that's going can pass over them
+        if let Some(motion) = wanted_motion.filter(|_| !ctx.is_pointer_over_area()) {
+            let anchor = (**cursor_ray).and_then(|ray| {
+                raycast
+                    .cast_ray(ray, &RaycastSettings::default())
+                    .first()
+                    .map(|(_, intersection)| {
+                        camera_transform
+                            .compute_matrix()
+                            .inverse()
+                            .transform_point3(intersection.position())
+                            .as_dvec3()
+                    })
+            });
+            // Taking over from a zoom to fit
+            commands.entity(camera).remove::<CameraFlight>();
+            match motion {
+                CameraMotion::Orbit => editor_camera.start_orbit(anchor),
+                CameraMotion::Pan => editor_camera.start_pan(anchor),
+                // Orthographic zoom changes the scale instead, below
+                CameraMotion::Zoom if orthographic => {}
+                CameraMotion::Zoom => editor_camera.start_zoom(anchor),
+            }
+            *current_motion = Some(motion);
+        }
+    }
+
+    let zoom = if scroll != 0.0 {
+        scroll
+    } else {
+        -cursor_delta.y * 0.01
+    };
+    match *current_motion {
+        Some(CameraMotion::Orbit) => editor_camera.send_screenspace_input(cursor_delta),
+        Some(CameraMotion::Pan) => {
+            editor_camera.send_screenspace_input(cursor_delta * settings.translate_sensitivity)
         }
-        editor_camera.end_move();
-        // editor_camera.enabled_motion.pan = false;
+        Some(CameraMotion::Zoom) => match &mut *projection {
+            Projection::Orthographic(orthographic) => zoom_orthographic(
+                &mut transform,
+                orthographic,
+                cursor_ndc,
+                zoom * settings.zoom_sensitivity,
+            ),
+            Projection::Perspective(_) => editor_camera.send_zoom_input(zoom),
+        },
+        None => {}
     }
 }
 
-fn switch_projection(
-    mut camera_query: Query<&mut Projection, With<Camera3d>>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+/// Scales an orthographic view around the cursor, so the point under it stays put
+fn zoom_orthographic(
+    transform: &mut Transform,
+    orthographic: &mut OrthographicProjection,
+    cursor_ndc: Vec2,
+    amount: f32,
+) {
+    let factor = (-amount * 0.1).exp();
+    let half_size = orthographic.area.size() / 2.0;
+    let cursor_offset = *transform.right() * cursor_ndc.x * half_size.x
+        + *transform.up() * cursor_ndc.y * half_size.y;
+    orthographic.scale *= factor;
+    transform.translation += cursor_offset * (1.0 - factor);
+}
+
+/// An orthographic projection where `scale` is half the height of the view, in inches
+pub fn orthographic(scale: f32) -> Projection {
+    Projection::Orthographic(OrthographicProjection {
+        scale,
+        scaling_mode: ScalingMode::FixedVertical(2.0),
+        // Orbiting can swing parts behind the camera, they should still show up
+        near: -1000.0,
+        far: 1000.0,
+        ..default()
+    })
+}
+
+/// The camera's projection as it is right now. `Camera::projection_matrix` only catches up after
+/// `Update`, so the gizmo would lag a frame behind an orthographic zoom
+pub fn projection_matrix(camera: &Camera, projection: &Projection) -> Mat4 {
+    let mut projection = projection.clone();
+    if let Some(size) = camera.logical_viewport_size() {
+        projection.update(size.x, size.y);
+    }
+    projection.get_projection_matrix()
+}
+
+/// Views looking straight along an axis, or from a corner
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StandardView {
+    Front,
+    Back,
+    Left,
+    Right,
+    Top,
+    Bottom,
+    Isometric,
+}
+
+impl StandardView {
+    pub const ALL: [StandardView; 7] = [
+        Self::Front,
+        Self::Back,
+        Self::Left,
+        Self::Right,
+        Self::Top,
+        Self::Bottom,
+        Self::Isometric,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Front => "Front",
+            Self::Back => "Back",
+            Self::Left => "Left",
+            Self::Right => "Right",
+            Self::Top => "Top",
+            Self::Bottom => "Bottom",
+            Self::Isometric => "Iso",
+        }
+    }
+
+    /// Direction the camera looks in, +Y is up and the front is on the +Z side
+    pub fn facing(&self) -> Direction3d {
+        match self {
+            Self::Front => Direction3d::NEG_Z,
+            Self::Back => Direction3d::Z,
+            Self::Left => Direction3d::X,
+            Self::Right => Direction3d::NEG_X,
+            Self::Top => Direction3d::NEG_Y,
+            Self::Bottom => Direction3d::Y,
+            Self::Isometric => Direction3d::new(Vec3::NEG_ONE).unwrap(),
+        }
+    }
+
+    fn action(&self) -> InputAction {
+        match self {
+            Self::Front => InputAction::ViewFront,
+            Self::Back => InputAction::ViewBack,
+            Self::Left => InputAction::ViewLeft,
+            Self::Right => InputAction::ViewRight,
+            Self::Top => InputAction::ViewTop,
+            Self::Bottom => InputAction::ViewBottom,
+            Self::Isometric => InputAction::ViewIsometric,
+        }
+    }
+
+    /// Smoothly turns the camera to this view, around the point it's anchored to
+    pub fn look_to(
+        &self,
+        camera: Entity,
+        camera_transform: &Transform,
+        editor_camera: &EditorCam,
+    ) -> LookToTrigger {
+        LookToTrigger::auto_snap_up_direction(
+            self.facing(),
+            camera,
+            camera_transform,
+            editor_camera,
+        )
+    }
+}
+
+fn standard_view_keys(
+    actions: Res<ActionState>,
+    camera_query: Query<(Entity, &Transform, &EditorCam)>,
+    mut look_to: EventWriter<LookToTrigger>,
 ) {
-    if !keyboard.just_pressed(KeyCode::KeyO) {
+    let Ok((camera, camera_transform, editor_camera)) = camera_query.get_single() else {
         return;
+    };
+    for view in StandardView::ALL {
+        if actions.just_pressed(view.action()) {
+            look_to.send(view.look_to(camera, camera_transform, editor_camera));
+        }
     }
-    for mut projection in camera_query.iter_mut() {
-        *projection = match *projection {
-            Projection::Perspective(_) => Projection::Orthographic(OrthographicProjection {
-                scale: 5.5,
-                scaling_mode: bevy::render::camera::ScalingMode::FixedVertical(2.0),
-                ..default()
-            }),
-            Projection::Orthographic(_) => Projection::default(),
+}
+
+/// Fits something in view, keeping the direction the camera looks in
+#[derive(Event, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FrameCamera {
+    /// Every visible part
+    All,
+    /// The selection, or everything if nothing is selected
+    Selection,
+}
+
+/// How long zooming to fit takes, in seconds
+const FLIGHT_TIME: f32 = 0.4;
+
+/// The camera smoothly moving from one place to another
+#[derive(Component, Debug)]
+pub struct CameraFlight {
+    from: Transform,
+    to: Transform,
+    /// Orthographic scale, unused in perspective
+    from_scale: f32,
+    to_scale: f32,
+    progress: f32,
+}
+
+impl CameraFlight {
+    pub fn new(from: Transform, to: Transform, from_scale: f32, to_scale: f32) -> Self {
+        Self {
+            from,
+            to,
+            from_scale,
+            to_scale,
+            progress: 0.0,
         }
     }
 }
+
+fn frame_keys(actions: Res<ActionState>, mut frame_events: EventWriter<FrameCamera>) {
+    if actions.just_pressed(InputAction::FrameAll) {
+        frame_events.send(FrameCamera::All);
+    }
+    if actions.just_pressed(InputAction::FrameSelection) {
+        frame_events.send(FrameCamera::Selection);
+    }
+}
+
+fn frame_camera(
+    mut commands: Commands,
+    mut frame_events: EventReader<FrameCamera>,
+    mut camera_query: Query<(Entity, &Transform, &Projection, &mut EditorCam)>,
+    part_query: Query<(Entity, &InheritedVisibility), With<PartName>>,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+) {
+    let Some(frame) = frame_events.read().last().copied() else {
+        return;
+    };
+    let Ok((camera, transform, projection, mut editor_camera)) = camera_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let mut targets: Vec<Entity> = match frame {
+        FrameCamera::Selection => selected_query.iter().collect(),
+        FrameCamera::All => Vec::new(),
+    };
+    if targets.is_empty() {
+        targets = part_query
+            .iter()
+            .filter(|(_, visibility)| visibility.get())
+            .map(|(entity, _)| entity)
+            .collect();
+    }
+    let Some((min, max)) = targets
+        .into_iter()
+        .filter_map(|entity| world_bounds(entity, &children_query, &aabb_query))
+        .reduce(|(min_a, max_a), (min_b, max_b)| (min_a.min(min_b), max_a.max(max_b)))
+    else {
+        return;
+    };
+    let center = (min + max) / 2.0;
+    // A little room around the edges
+    let radius = ((max - min).length() / 2.0).max(0.5) * 1.1;
+
+    let forward = *transform.forward();
+    let mut to = *transform;
+    let (from_scale, to_scale) = match projection {
+        Projection::Perspective(perspective) => {
+            let vertical = perspective.fov;
+            let horizontal = 2.0 * ((vertical / 2.0).tan() * perspective.aspect_ratio).atan();
+            let distance = radius / (vertical.min(horizontal) / 2.0).sin();
+            to.translation = center - forward * distance;
+            (1.0, 1.0)
+        }
+        Projection::Orthographic(orthographic) => {
+            // The distance doesn't change the size in orthographic, it just has to stay in front
+            let distance = (transform.translation - center)
+                .dot(forward)
+                .abs()
+                .max(radius * 2.0);
+            to.translation = center - forward * distance;
+            let shown = orthographic.area.width().min(orthographic.area.height()) / 2.0;
+            (orthographic.scale, orthographic.scale * radius / shown)
+        }
+    };
+
+    editor_camera.end_move();
+    commands
+        .entity(camera)
+        .insert(CameraFlight::new(*transform, to, from_scale, to_scale));
+}
+
+fn fly_camera(
+    mut commands: Commands,
+    time: Res<Time>,
+    mut camera_query: Query<(Entity, &mut Transform, &mut Projection, &mut CameraFlight)>,
+) {
+    for (camera, mut transform, mut projection, mut flight) in camera_query.iter_mut() {
+        flight.progress = (flight.progress + time.delta_seconds() / FLIGHT_TIME).min(1.0);
+        let eased = flight.progress * flight.progress * (3.0 - 2.0 * flight.progress);
+        transform.translation = flight.from.translation.lerp(flight.to.translation, eased);
+        transform.rotation = flight.from.rotation.slerp(flight.to.rotation, eased);
+        if let Projection::Orthographic(orthographic) = &mut *projection {
+            orthographic.scale = flight.from_scale + (flight.to_scale - flight.from_scale) * eased;
+        }
+        if flight.progress >= 1.0 {
+            commands.entity(camera).remove::<CameraFlight>();
+        }
+    }
+}
+
+/// Keeps the camera's sensitivity in line with the settings as they're edited
+fn apply_camera_settings(mut editor_camera: Query<&mut EditorCam>, settings: Res<Settings>) {
+    for mut editor_camera in editor_camera.iter_mut() {
+        editor_camera.sensitivity.orbit = settings.rotate_sensitivity;
+        editor_camera.sensitivity.zoom = settings.zoom_sensitivity;
+    }
+}
+/// Whatever is in the middle of the screen stays the same size when switching
+fn switch_projection(
+    mut camera_query: Query<(&mut Transform, &mut Projection), With<Camera3d>>,
+    actions: Res<ActionState>,
+    mut raycast: Raycast,
+) {
+    if !actions.just_pressed(InputAction::ToggleProjection) {
+        return;
+    }
+    for (mut transform, mut projection) in camera_query.iter_mut() {
+        let forward = *transform.forward();
+        let focus_distance = raycast
+            .cast_ray(
+                Ray3d::new(transform.translation, forward),
+                &RaycastSettings::default(),
+            )
+            .first()
+            .map(|(_, intersection)| intersection.distance())
+            .unwrap_or(10.0);
+        *projection = match &*projection {
+            Projection::Perspective(perspective) => {
+                orthographic(focus_distance * (perspective.fov / 2.0).tan())
+            }
+            Projection::Orthographic(orthographic) => {
+                let perspective = PerspectiveProjection::default();
+                let distance = orthographic.scale / (perspective.fov / 2.0).tan();
+                transform.translation += forward * (focus_distance - distance);
+                Projection::Perspective(perspective)
+            }
+        };
+    }
+}
@@ -0,0 +1,220 @@
+use std::f32::consts::TAU;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constraints::PendingConstraints,
+    placing::{Part, PartName},
+    units::HOLE_PITCH,
+};
+
+mod ui;
+use ui::PatternUiPlugin;
+
+pub struct PatternPlugin;
+
+impl Plugin for PatternPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_plugins(PatternUiPlugin).add_systems(
+            Update,
+            update_patterns.run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum PatternKind {
+    /// `count` copies (including the original) spaced `spacing` apart along `direction`
+    Linear {
+        direction: Vec3,
+        spacing: f32,
+        count: u32,
+    },
+    /// `count` copies (including the original) spread over `angle` radians around the axis going
+    /// through `center`
+    Circular {
+        center: Vec3,
+        axis: Vec3,
+        angle: f32,
+        count: u32,
+    },
+}
+
+impl PatternKind {
+    pub fn default_linear() -> Self {
+        Self::Linear {
+            direction: Vec3::X,
+            spacing: HOLE_PITCH,
+            count: 2,
+        }
+    }
+
+    pub fn default_circular() -> Self {
+        Self::Circular {
+            center: Vec3::ZERO,
+            axis: Vec3::Y,
+            angle: TAU,
+            count: 4,
+        }
+    }
+
+    pub fn count(&self) -> u32 {
+        match *self {
+            Self::Linear { count, .. } => count,
+            Self::Circular { count, .. } => count,
+        }
+    }
+
+    /// Transform of the `index`th copy of a part whose original transform is `source`.
+    /// Index 0 is the original part itself
+    pub fn instance_transform(&self, source: Transform, index: u32) -> Transform {
+        match *self {
+            Self::Linear {
+                direction, spacing, ..
+            } => {
+                let offset = direction.normalize_or_zero() * spacing * index as f32;
+                Transform {
+                    translation: source.translation + offset,
+                    ..source
+                }
+            }
+            Self::Circular {
+                center,
+                axis,
+                angle,
+                count,
+            } => {
+                // A full circle would put the last copy on top of the original
+                let full_circle = (angle.abs() - TAU).abs() < 1e-3;
+                let steps = if full_circle {
+                    count.max(1)
+                } else {
+                    count.saturating_sub(1).max(1)
+                };
+                let axis = axis.try_normalize().unwrap_or(Vec3::Y);
+                let rotation = Quat::from_axis_angle(axis, angle / steps as f32 * index as f32);
+                let mut transform = source;
+                transform.rotate_around(center, rotation);
+                transform
+            }
+        }
+    }
+}
+
+/// A pattern stays linked to the parts it was made from, so changing either the parameters or
+/// moving a source part regenerates the copies
+#[derive(Component, Debug, Clone)]
+pub struct PatternFeature {
+    pub sources: Vec<Entity>,
+    pub kind: PatternKind,
+    /// `instances[copy][source]`, copy 0 (the sources themselves) isn't stored
+    instances: Vec<Vec<Entity>>,
+}
+
+impl PatternFeature {
+    pub fn new(sources: Vec<Entity>, kind: PatternKind) -> Self {
+        Self {
+            sources,
+            kind,
+            instances: vec![],
+        }
+    }
+
+    pub fn instances(&self) -> impl Iterator<Item = &Entity> {
+        self.instances.iter().flatten()
+    }
+}
+
+/// Marks a part that was generated by the pattern feature it points to
+#[derive(Component, Debug, Clone, Copy)]
+pub struct PatternInstance(pub Entity);
+
+fn update_patterns(
+    mut commands: Commands,
+    mut feature_query: Query<(Entity, &mut PatternFeature)>,
+    source_query: Query<
+        (&Transform, &PartName, &Handle<Scene>),
+        (With<Part>, Without<PatternInstance>),
+    >,
+    changed_source_query: Query<(), (Changed<Transform>, With<Part>, Without<PatternInstance>)>,
+    mut instance_query: Query<&mut Transform, With<PatternInstance>>,
+) {
+    for (feature_entity, mut feature) in feature_query.iter_mut() {
+        // Parts that got deleted or undone can't be patterned anymore
+        let sources_before = feature.sources.len();
+        feature
+            .bypass_change_detection()
+            .sources
+            .retain(|source| source_query.contains(*source));
+        let sources_removed = feature.sources.len() != sources_before;
+
+        if feature.sources.is_empty() {
+            despawn_pattern(&mut commands, feature_entity, &feature);
+            continue;
+        }
+
+        let sources_moved = feature
+            .sources
+            .iter()
+            .any(|source| changed_source_query.contains(*source));
+        if !feature.is_changed() && !sources_moved && !sources_removed {
+            continue;
+        }
+
+        let copies = feature.kind.count().saturating_sub(1) as usize;
+        let needs_respawn = sources_removed
+            || feature.instances.len() != copies
+            || feature
+                .instances
+                .iter()
+                .any(|copy| copy.len() != feature.sources.len());
+
+        let feature = feature.bypass_change_detection();
+        if needs_respawn {
+            for instance in feature.instances.drain(..).flatten() {
+                commands.entity(instance).despawn_recursive();
+            }
+            for index in 1..=copies as u32 {
+                let mut copy = vec![];
+                for source in feature.sources.iter() {
+                    let (transform, name, scene) = source_query.get(*source).unwrap();
+                    let instance = commands
+                        .spawn((
+                            SceneBundle {
+                                scene: scene.clone(),
+                                transform: feature.kind.instance_transform(*transform, index),
+                                ..default()
+                            },
+                            Part,
+                            PartName(name.0.clone()),
+                            PatternInstance(feature_entity),
+                            PendingConstraints,
+                        ))
+                        .id();
+                    copy.push(instance);
+                }
+                feature.instances.push(copy);
+            }
+            continue;
+        }
+
+        for (index, copy) in feature.instances.iter().enumerate() {
+            for (source, instance) in feature.sources.iter().zip(copy.iter()) {
+                let (transform, _, _) = source_query.get(*source).unwrap();
+                if let Ok(mut instance_transform) = instance_query.get_mut(*instance) {
+                    *instance_transform = feature
+                        .kind
+                        .instance_transform(*transform, index as u32 + 1);
+                }
+            }
+        }
+    }
+}
+
+pub fn despawn_pattern(commands: &mut Commands, feature_entity: Entity, feature: &PatternFeature) {
+    for instance in feature.instances() {
+        commands.entity(*instance).despawn_recursive();
+    }
+    commands.entity(feature_entity).despawn_recursive();
+}
@@ -0,0 +1,199 @@
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+use crate::{
+    actions::{Action, ActionList},
+    keybinds::{ActionState, InputAction},
+    move_objects::CurrentlyMoving,
+    settings::Settings,
+    units::{LengthUnit, HOLE_PITCH},
+};
+
+use super::{despawn_pattern, PatternFeature, PatternInstance, PatternKind};
+
+pub struct PatternUiPlugin;
+
+impl Plugin for PatternUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PatternEditor>().add_systems(
+            Update,
+            (start_pattern, pattern_window).run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+struct PatternEditor {
+    open: bool,
+    editing: Option<Entity>,
+}
+
+/// Patterns the current selection, or toggles the pattern list when nothing is selected
+fn start_pattern(
+    mut commands: Commands,
+    actions: Res<ActionState>,
+    selected_query: Query<Entity, (With<CurrentlyMoving>, Without<PatternInstance>)>,
+    mut editor: ResMut<PatternEditor>,
+    mut action_list: ResMut<ActionList>,
+) {
+    if !actions.just_pressed(InputAction::Pattern) {
+        return;
+    }
+    let sources: Vec<Entity> = selected_query.iter().collect();
+    if sources.is_empty() {
+        editor.open = !editor.open;
+        return;
+    }
+    let feature = commands
+        .spawn(PatternFeature::new(sources, PatternKind::default_linear()))
+        .id();
+    action_list.0.push(Action::Patterned(feature));
+    editor.open = true;
+    editor.editing = Some(feature);
+}
+
+fn pattern_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut editor: ResMut<PatternEditor>,
+    mut feature_query: Query<(Entity, &mut PatternFeature)>,
+    settings: Res<Settings>,
+) {
+    if !editor.open {
+        return;
+    }
+    let mut open = true;
+    egui::Window::new("Pattern")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            for (index, (entity, feature)) in feature_query.iter().enumerate() {
+                let label = format!(
+                    "Pattern {} ({}, {}x)",
+                    index + 1,
+                    match feature.kind {
+                        PatternKind::Linear { .. } => "Linear",
+                        PatternKind::Circular { .. } => "Circular",
+                    },
+                    feature.kind.count()
+                );
+                if ui
+                    .selectable_label(editor.editing == Some(entity), label)
+                    .clicked()
+                {
+                    editor.editing = Some(entity);
+                }
+            }
+            ui.separator();
+
+            let Some(editing) = editor.editing else {
+                ui.label("Select parts and press N to pattern them");
+                return;
+            };
+            let Ok((_, mut feature)) = feature_query.get_mut(editing) else {
+                editor.editing = None;
+                return;
+            };
+
+            // Only write back real edits, otherwise the pattern would regenerate every frame
+            let mut kind = feature.kind;
+            edit_pattern_kind(ui, &mut kind, settings.unit);
+            if kind != feature.kind {
+                feature.kind = kind;
+            }
+
+            ui.separator();
+            if ui.button("Remove pattern").clicked() {
+                despawn_pattern(&mut commands, editing, &feature);
+                editor.editing = None;
+            }
+        });
+    if !open {
+        editor.open = false;
+    }
+}
+
+fn edit_pattern_kind(ui: &mut egui::Ui, kind: &mut PatternKind, unit: LengthUnit) {
+    ui.horizontal(|ui| {
+        let is_linear = matches!(kind, PatternKind::Linear { .. });
+        if ui.selectable_label(is_linear, "Linear").clicked() && !is_linear {
+            *kind = PatternKind::default_linear();
+        }
+        if ui.selectable_label(!is_linear, "Circular").clicked() && is_linear {
+            *kind = PatternKind::default_circular();
+        }
+    });
+
+    match kind {
+        PatternKind::Linear {
+            direction,
+            spacing,
+            count,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Direction");
+                edit_vec3(ui, direction);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Spacing");
+                let mut shown = unit.from_inches(*spacing);
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut shown)
+                            .speed(0.05)
+                            .suffix(unit.suffix()),
+                    )
+                    .changed()
+                {
+                    *spacing = unit.to_inches(shown);
+                }
+                if ui.button("Hole pitch").clicked() {
+                    *spacing = HOLE_PITCH;
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Count");
+                ui.add(egui::DragValue::new(count).clamp_range(1..=100));
+            });
+        }
+        PatternKind::Circular {
+            center,
+            axis,
+            angle,
+            count,
+        } => {
+            ui.horizontal(|ui| {
+                ui.label("Center");
+                edit_vec3(ui, center);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Axis");
+                edit_vec3(ui, axis);
+            });
+            ui.horizontal(|ui| {
+                ui.label("Angle");
+                let mut degrees = angle.to_degrees();
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut degrees)
+                            .clamp_range(-360.0..=360.0)
+                            .suffix("°"),
+                    )
+                    .changed()
+                {
+                    *angle = degrees.to_radians();
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Count");
+                ui.add(egui::DragValue::new(count).clamp_range(1..=100));
+            });
+        }
+    }
+}
+
+fn edit_vec3(ui: &mut egui::Ui, value: &mut Vec3) {
+    ui.add(egui::DragValue::new(&mut value.x).speed(0.05).prefix("x: "));
+    ui.add(egui::DragValue::new(&mut value.y).speed(0.05).prefix("y: "));
+    ui.add(egui::DragValue::new(&mut value.z).speed(0.05).prefix("z: "));
+}
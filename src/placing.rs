@@ -3,6 +3,7 @@ use bevy::prelude::*;
 use bevy_mod_raycast::prelude::*;
 
 use crate::constraints::ConstrainState;
+use crate::keybinds::{ActionState, InputAction};
 
 use crate::ui::editor::handle::ModelHandles;
 
@@ -49,11 +50,14 @@ fn spawn_event(
         let new_position = Vec3::new(0.0, -10000.0, 0.0); // Out of the camera's view lmfao
         let name = &event.0;
         let handle = event.1.clone();
+        // Scaled once here, copies (patterns, mirrors, assemblies) take it from the original
+        let import_scale = crate::catalog::import_scale(name);
 
         commands.spawn((
             SceneBundle {
                 scene: handle.clone(),
-                transform: Transform::from_translation(new_position),
+                transform: Transform::from_translation(new_position)
+                    .with_scale(Vec3::splat(import_scale)),
                 ..default()
             },
             CurrentlyPlacing,
@@ -79,6 +83,22 @@ pub struct Part;
 
 const PLACING_RADIUS: f32 = 30.0;
 
+/// Walks up the hierarchy from `entity` (usually a mesh that got hit by a raycast) until it finds
+/// the placed part that owns it
+pub fn find_part_root(
+    entity: Entity,
+    parent_query: &Query<&Parent>,
+    is_part: impl Fn(Entity) -> bool,
+) -> Option<Entity> {
+    let mut current = entity;
+    loop {
+        if is_part(current) {
+            return Some(current);
+        }
+        current = parent_query.get(current).ok()?.get();
+    }
+}
+
 fn placing(
     mut commands: Commands,
     mut placing_query: Query<(&mut Transform, &PartName, Entity), With<CurrentlyPlacing>>,
@@ -114,40 +134,45 @@ fn placing(
             );
         }
         if let Some(cursor_ray) = **cursor_ray {
-            let intersection_array = &raycast.cast_ray(
-                cursor_ray,
-                &RaycastSettings {
-                    filter: &|filter_entity| {
-                        if let Ok(_worked) = placed_query.get(filter_entity) {
-                            return true;
-                        }
-                        false
-                    },
-                    ..default()
-                },
-            );
-            if intersection_array.is_empty() {
-                transform.translation = cursor_ray.get_point(PLACING_RADIUS);
-                continue;
-            }
-            let intersection_data = &intersection_array[0].1;
-            if intersection_data.distance() >= PLACING_RADIUS {
-                transform.translation = cursor_ray.get_point(PLACING_RADIUS);
-                continue;
-            }
-            transform.translation = intersection_data.position();
+            transform.translation = placing_point(cursor_ray, &mut raycast, &|filter_entity| {
+                placed_query.contains(filter_entity)
+            });
         }
     }
 }
 
+/// Where something being placed should go: the first hit under the cursor, or `PLACING_RADIUS`
+/// along the cursor ray if there's nothing close enough
+pub fn placing_point(
+    cursor_ray: Ray3d,
+    raycast: &mut Raycast,
+    filter: &dyn Fn(Entity) -> bool,
+) -> Vec3 {
+    let intersection_array = raycast.cast_ray(
+        cursor_ray,
+        &RaycastSettings {
+            filter,
+            ..default()
+        },
+    );
+    if intersection_array.is_empty() {
+        return cursor_ray.get_point(PLACING_RADIUS);
+    }
+    let intersection_data = &intersection_array[0].1;
+    if intersection_data.distance() >= PLACING_RADIUS {
+        return cursor_ray.get_point(PLACING_RADIUS);
+    }
+    intersection_data.position()
+}
+
 fn stop_placing_mode(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut placing_state: ResMut<NextState<PlacingState>>,
     mut action_list: ResMut<ActionList>,
     mut commands: Commands,
     mut placing_query: Query<Entity, With<CurrentlyPlacing>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Cancel) {
         placing_state.set(PlacingState::NotPlacing);
         action_list.0.push(Action::PlaceHolder);
         for part in placing_query.iter_mut() {
@@ -0,0 +1,121 @@
+use bevy::{ecs::system::EntityCommands, prelude::*};
+
+use crate::{constraints::ConstrainComponent, placing::PartName};
+
+pub struct PropertiesPlugin;
+
+impl Plugin for PropertiesPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            apply_color_overrides.run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+/// Draws the whole part in this color instead of the colors of its model
+#[derive(Component, Debug, Clone, Copy, PartialEq)]
+pub struct ColorOverride(pub Color);
+
+/// Free text the user wrote about a part
+#[derive(Component, Debug, Clone, Default, PartialEq)]
+pub struct Notes(pub String);
+
+/// Material a mesh had before its part's color was overridden
+#[derive(Component)]
+struct OriginalMaterial(Handle<StandardMaterial>);
+
+/// Everything the inspector can change about a part besides its transform, so an edit can be undone
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct PartProperties {
+    /// Custom name, `None` uses the catalog name
+    pub name: Option<String>,
+    pub color: Option<Color>,
+    pub notes: String,
+}
+
+impl PartProperties {
+    pub fn new(name: Option<&Name>, color: Option<&ColorOverride>, notes: Option<&Notes>) -> Self {
+        Self {
+            name: name.map(|name| name.to_string()),
+            color: color.map(|color| color.0),
+            notes: notes.map(|notes| notes.0.clone()).unwrap_or_default(),
+        }
+    }
+
+    pub fn apply(&self, entity_commands: &mut EntityCommands) {
+        match self.name.as_ref().filter(|name| !name.trim().is_empty()) {
+            Some(name) => entity_commands.insert(Name::new(name.clone())),
+            None => entity_commands.remove::<Name>(),
+        };
+        match self.color {
+            Some(color) => entity_commands.insert(ColorOverride(color)),
+            None => entity_commands.remove::<ColorOverride>(),
+        };
+        if self.notes.is_empty() {
+            entity_commands.remove::<Notes>();
+        } else {
+            entity_commands.insert(Notes(self.notes.clone()));
+        }
+    }
+}
+
+/// Swaps the materials of overridden parts for a copy in the override color (and back again once
+/// the override is removed). Each mesh gets one copy, which is recolored when the color changes.
+/// Runs every frame since a part's scene can finish loading at any time
+fn apply_color_overrides(
+    mut commands: Commands,
+    part_query: Query<(Entity, &ColorOverride), With<PartName>>,
+    children_query: Query<&Children>,
+    mut mesh_query: Query<
+        (&mut Handle<StandardMaterial>, Option<&OriginalMaterial>),
+        Without<ConstrainComponent>,
+    >,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut removed_overrides: RemovedComponents<ColorOverride>,
+) {
+    for (part, ColorOverride(color)) in part_query.iter() {
+        for descendant in children_query.iter_descendants(part) {
+            let Ok((mut handle, original)) = mesh_query.get_mut(descendant) else {
+                continue;
+            };
+            if materials
+                .get(handle.id())
+                .is_some_and(|material| material.base_color == *color)
+            {
+                continue;
+            }
+            let original = match original {
+                // The mesh already has its own copy, so recolor that instead of adding another
+                Some(original) if original.0 != *handle => {
+                    if let Some(material) = materials.get_mut(handle.id()) {
+                        material.base_color = *color;
+                        continue;
+                    }
+                    original.0.clone()
+                }
+                Some(original) => original.0.clone(),
+                None => {
+                    commands
+                        .entity(descendant)
+                        .insert(OriginalMaterial(handle.clone()));
+                    handle.clone()
+                }
+            };
+            let material = StandardMaterial {
+                base_color: *color,
+                ..materials.get(original.id()).cloned().unwrap_or_default()
+            };
+            *handle = materials.add(material);
+        }
+    }
+
+    for part in removed_overrides.read() {
+        for descendant in children_query.iter_descendants(part) {
+            if let Ok((mut handle, Some(original))) = mesh_query.get_mut(descendant) {
+                *handle = original.0.clone();
+                commands.entity(descendant).remove::<OriginalMaterial>();
+            }
+        }
+    }
+}
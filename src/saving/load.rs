@@ -0,0 +1,89 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+
+use crate::assembly::{remove_unknown_parts, spawn_node, AssemblyNode};
+use crate::catalog::find_by_scene_path;
+use crate::pattern::PatternFeature;
+use crate::ui::editor::handle::ModelHandles;
+
+use super::save::{SaveContents, SavedPattern, SAVE_FILE};
+
+/// Only the parts, assemblies and patterns are read here, the rest of the project file is read
+/// by what it belongs to
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProjectContents {
+    parts: Vec<SaveContents>,
+    assemblies: Vec<AssemblyNode>,
+    patterns: Vec<SavedPattern>,
+}
+
+/// Puts the parts, assemblies and patterns from the project file back the first time the editor
+/// opens
+pub fn load_project(
+    mut commands: Commands,
+    mut loaded: Local<bool>,
+    model_handles: Res<ModelHandles>,
+) {
+    if *loaded {
+        return;
+    }
+    *loaded = true;
+    let Ok(contents) = std::fs::read_to_string(SAVE_FILE) else {
+        return;
+    };
+    // Older projects are just a list of parts
+    let project = ron::from_str::<ProjectContents>(&contents).or_else(|_| {
+        ron::from_str::<Vec<SaveContents>>(&contents)
+            .map(|parts| ProjectContents { parts, ..default() })
+    });
+    let project = match project {
+        Ok(project) => project,
+        Err(error) => {
+            error!("Couldn't read {SAVE_FILE}: {error}");
+            return;
+        }
+    };
+
+    // Parts from a newer version, or that were taken out of the catalog, can't be spawned
+    let mut unknown = vec![];
+    // Patterns point at the loose parts by their place in the list
+    let loose_parts: Vec<Option<Entity>> = project
+        .parts
+        .into_iter()
+        .map(|part| {
+            if find_by_scene_path(&part.part_name).is_none() {
+                unknown.push(part.part_name);
+                return None;
+            }
+            let node = AssemblyNode::Part {
+                part_name: part.part_name,
+                transform: part.transform,
+            };
+            Some(spawn_node(&mut commands, &node, &model_handles))
+        })
+        .collect();
+    let mut assemblies = project.assemblies;
+    unknown.extend(remove_unknown_parts(&mut assemblies));
+    if !unknown.is_empty() {
+        warn!(
+            "Left out parts that aren't in the catalog: {}",
+            unknown.join(", ")
+        );
+    }
+    for node in assemblies.iter() {
+        spawn_node(&mut commands, node, &model_handles);
+    }
+
+    // The copies come back once `update_patterns` sees the new feature
+    for pattern in project.patterns {
+        let sources: Vec<Entity> = pattern
+            .sources
+            .iter()
+            .filter_map(|index| loose_parts.get(*index).copied().flatten())
+            .collect();
+        if !sources.is_empty() {
+            commands.spawn(PatternFeature::new(sources, pattern.kind));
+        }
+    }
+}
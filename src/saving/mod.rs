@@ -1,13 +1,34 @@
 use bevy::prelude::*;
 
-use crate::{actions::ActionList, ui};
+use crate::{
+    actions::ActionList,
+    keybinds::{ActionState, InputAction},
+    ui::{self, editor::handle::ModelHandles},
+};
 
 pub mod save_timer;
 use save_timer::*;
 
 mod save;
+pub use save::SAVE_FILE;
 use save::*;
 
+mod load;
+use load::load_project;
+
+/// Keeps names usable as file names on every system, and inside the folder they're saved to
+pub fn file_name(name: &str) -> String {
+    name.chars()
+        .map(|character| {
+            if character.is_alphanumeric() || matches!(character, '-' | '_' | ' ') {
+                character
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
 pub struct SavingPlugin;
 
 impl Plugin for SavingPlugin {
@@ -18,6 +39,7 @@ impl Plugin for SavingPlugin {
             .add_systems(
                 Update,
                 (
+                    load_project.run_if(resource_exists::<ModelHandles>),
                     save_event.run_if(on_event::<SaveEvent>()),
                     time_since_last_save,
                     check_for_save_key,
@@ -30,10 +52,10 @@ impl Plugin for SavingPlugin {
 
 fn check_for_save_key(
     mut save_event_writer: EventWriter<SaveEvent>,
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut icon_query: Query<&mut Visibility, With<SaveIcon>>,
 ) {
-    if keyboard.just_pressed(KeyCode::KeyS) && keyboard.pressed(KeyCode::ControlLeft) {
+    if actions.just_pressed(InputAction::Save) {
         save_event_writer.send(SaveEvent);
         let mut icon_visibility = icon_query.get_single_mut().unwrap();
         *icon_visibility = Visibility::Hidden;
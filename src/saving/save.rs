@@ -1,25 +1,53 @@
 use bevy::prelude::*;
 
-use serde::Serialize;
+use serde::{Deserialize, Serialize};
 
-use std::fs::{File, OpenOptions};
+use std::fs::File;
 use std::io::Write;
 
 use chrono::prelude::DateTime;
 use chrono::Local;
 
-use crate::placing::{self, PartName};
+use crate::assembly::{assembly_to_node, Assembly, AssemblyNode};
+use crate::catalog::import_scale;
+use crate::measure::{Measurement, Measurements};
+use crate::pattern::{PatternFeature, PatternInstance, PatternKind};
+use crate::placing::{self, CurrentlyPlacing, PartName};
 use crate::saving::save_timer;
+use crate::views::{SavedView, SavedViews};
+
+/// The project is saved here, next to the program
+pub const SAVE_FILE: &str = "save.ron";
 
 #[derive(Event)]
 pub struct SaveEvent;
 
-#[derive(Serialize, Clone, Debug)]
-struct SaveContents {
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SaveContents {
     pub part_name: String,
+    /// The scale is saved without the part's import scale, so it's 1 for parts at their real size
     pub transform: (Vec3, Vec4, Vec3),
 }
 
+/// Pattern copies aren't saved, they're made again from the pattern when the project is loaded
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SavedPattern {
+    /// Indices into the saved parts that aren't in an assembly
+    pub sources: Vec<usize>,
+    pub kind: PatternKind,
+}
+
+#[derive(Serialize, Clone, Debug)]
+struct ProjectFile<'a> {
+    /// Parts that aren't in an assembly
+    parts: Vec<SaveContents>,
+    /// Assemblies with everything in them, relative to the assembly they're in
+    assemblies: Vec<AssemblyNode>,
+    patterns: Vec<SavedPattern>,
+    views: &'a [SavedView],
+    measurements: &'a [Measurement],
+}
+
 impl From<(&Transform, &PartName)> for SaveContents {
     fn from(value: (&Transform, &PartName)) -> Self {
         let transform = value.0;
@@ -29,37 +57,70 @@ impl From<(&Transform, &PartName)> for SaveContents {
             transform: (
                 transform.translation,
                 transform.rotation.to_array().into(),
-                transform.scale,
+                transform.scale / import_scale(&part_name.0),
             ),
         }
     }
 }
 
 pub fn save_event(
-    part_query: Query<(&Transform, &placing::PartName), With<placing::Part>>,
+    part_query: Query<
+        (Entity, &Transform, &placing::PartName),
+        (
+            With<placing::Part>,
+            Without<Parent>,
+            Without<PatternInstance>,
+        ),
+    >,
+    top_level_assembly_query: Query<
+        Entity,
+        (With<Assembly>, Without<Parent>, Without<CurrentlyPlacing>),
+    >,
+    assembly_query: Query<(&Assembly, &Transform, Option<&Children>)>,
+    assembly_part_query: Query<(&placing::PartName, &Transform)>,
+    pattern_query: Query<&PatternFeature>,
     mut most_recent_save: ResMut<save_timer::MostRecentSave>,
     mut update_save_count_timer: ResMut<crate::saving::UpdateSaveCountTimer>,
+    (saved_views, measurements): (Res<SavedViews>, Res<Measurements>),
 ) {
     let mut save_contents_arr: Vec<SaveContents> = Vec::new();
-    for value in part_query.iter() {
-        save_contents_arr.push(SaveContents::from(value));
-        println!("{:?} {:?}", value.0, value.1);
+    let mut part_entities: Vec<Entity> = Vec::new();
+    for (entity, transform, part_name) in part_query.iter() {
+        save_contents_arr.push(SaveContents::from((transform, part_name)));
+        part_entities.push(entity);
+        println!("{:?} {:?}", transform, part_name);
     }
+    // Sources in an assembly aren't kept in the pattern
+    let patterns = pattern_query
+        .iter()
+        .map(|feature| SavedPattern {
+            sources: feature
+                .sources
+                .iter()
+                .filter_map(|source| part_entities.iter().position(|entity| entity == source))
+                .collect(),
+            kind: feature.kind,
+        })
+        .filter(|pattern| !pattern.sources.is_empty())
+        .collect();
+    let assemblies = top_level_assembly_query
+        .iter()
+        .filter_map(|assembly| assembly_to_node(assembly, &assembly_query, &assembly_part_query))
+        .collect();
 
-    let file_name = "save".to_string();
+    let project = ProjectFile {
+        parts: save_contents_arr,
+        assemblies,
+        patterns,
+        views: &saved_views.0,
+        measurements: &measurements.list,
+    };
 
-    if let Ok(mut file) = OpenOptions::new()
-        .write(true)
-        .open(format!("{file_name}.ron"))
-    {
-        file.write_all(ron::ser::to_string(&save_contents_arr).unwrap().as_bytes())
-            .expect("Couldn't write to file");
-    } else {
-        let mut file = File::create(format!("{file_name}.ron"))
-            .expect(format!("Couldn't create {file_name}.ron file").as_str());
-        file.write_all(ron::ser::to_string(&save_contents_arr).unwrap().as_bytes())
-            .expect("Couldn't write to file");
-    }
+    // Creating the file empties it, so a shorter save doesn't leave the end of the last one behind
+    let mut file =
+        File::create(SAVE_FILE).expect(format!("Couldn't create {SAVE_FILE} file").as_str());
+    file.write_all(ron::ser::to_string(&project).unwrap().as_bytes())
+        .expect("Couldn't write to file");
 
     most_recent_save.set(std::time::SystemTime::now());
     update_save_count_timer
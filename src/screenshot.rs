@@ -2,6 +2,9 @@ use bevy::prelude::*;
 use bevy::render::view::screenshot::ScreenshotManager;
 use bevy::window::PrimaryWindow;
 use bevy_infinite_grid::InfiniteGrid;
+
+use crate::keybinds::{ActionState, InputAction};
+
 pub struct ScreenshotPlugin;
 
 impl Plugin for ScreenshotPlugin {
@@ -11,14 +14,14 @@ impl Plugin for ScreenshotPlugin {
 }
 
 fn screenshot_system(
-    input: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     main_window: Query<Entity, With<PrimaryWindow>>,
     mut screenshot_manager: ResMut<ScreenshotManager>,
     mut counter: Local<u32>,
     mut ui_query: Query<&mut Visibility, (With<Node>, Without<InfiniteGrid>)>,
     mut grid_query: Query<&mut Visibility, (With<InfiniteGrid>, Without<Node>)>,
 ) {
-    if input.just_pressed(KeyCode::F12) {
+    if actions.just_pressed(InputAction::Screenshot) {
         for mut visibility in grid_query.iter_mut() {
             *visibility = Visibility::Hidden;
         }
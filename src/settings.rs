@@ -1,30 +1,191 @@
 use bevy::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::fs::File;
-use std::io::Read;
-use std::io::Write;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
 
-#[derive(Resource, Serialize, Deserialize, Clone, Copy, Debug)]
+use crate::keybinds::Keybinds;
+use crate::units::LengthUnit;
+
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+// Settings files from older versions are missing the newer fields
+#[serde(default)]
 pub struct Settings {
+    /// Index of the camera's `ControlProfile`
     pub control_state: u8,
     pub rotate_sensitivity: Vec2,
     pub translate_sensitivity: Vec2,
     pub zoom_sensitivity: f32,
     pub fps_cap: f64,
+    /// Whether dragged parts snap, holding the `DisableSnapping` key does the opposite
     pub precision_snap: bool,
+    pub snap_mode: SnapMode,
+    /// Grid increment, and how close something has to be to snap to geometry, in inches
+    pub snap_distance: f32,
+    /// In degrees
+    pub snap_angle: f32,
+    pub keybinds: Keybinds,
+    pub theme: Theme,
+    /// How lengths are shown and typed in
+    pub unit: LengthUnit,
+    /// Name of the competition rules that robots are checked against
+    pub rule_set: String,
+    /// Fields this version doesn't know about (e.g. written by a newer version), kept as text so
+    /// they survive being saved again
+    #[serde(skip)]
+    unknown_fields: Vec<(String, String)>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Theme {
+    #[default]
+    Dark,
+    Light,
+}
+
+impl Theme {
+    pub const ALL: [Theme; 2] = [Self::Dark, Self::Light];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Dark => "Dark",
+            Self::Light => "Light",
+        }
+    }
+
+    /// Background behind the scene
+    pub fn clear_color(&self) -> Color {
+        match self {
+            Self::Dark => Color::hex("333333").unwrap(),
+            Self::Light => Color::hex("D8D8D8").unwrap(),
+        }
+    }
+}
+
+/// What a dragged part snaps to (holding Left-Shift turns snapping off)
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SnapMode {
+    /// Moves in steps of `snap_distance`
+    #[default]
+    Grid,
+    /// Moves in steps of one VEX hole
+    HolePitch,
+    /// Lines holes up with other parts' holes, or faces with their faces
+    Geometry,
+}
+
+impl SnapMode {
+    pub const ALL: [SnapMode; 3] = [Self::Grid, Self::HolePitch, Self::Geometry];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Grid => "Grid",
+            Self::HolePitch => "Hole pitch",
+            Self::Geometry => "Other parts",
+        }
+    }
 }
 
-/*
 impl Settings {
-    fn write_changes(&mut self) {
-        if let Ok(mut file) = File::create(".settings.ron") {
-            let settings_string = ron::ser::to_string(self).unwrap();
-            file.write_all(settings_string.as_bytes())
-                .expect("Couldn't write to file in load_or_create_settings_file");
+    pub fn write_changes(&self) {
+        let path = settings_path();
+        if let Some(folder) = path.parent() {
+            let _ = fs::create_dir_all(folder);
+        }
+        if let Err(error) = fs::write(&path, self.to_ron()) {
+            error!("Couldn't write settings to {}: {error}", path.display());
+        }
+    }
+
+    fn to_ron(&self) -> String {
+        let mut settings_string = ron::ser::to_string(self).unwrap();
+        // Put the unknown fields back in before the closing parenthesis
+        settings_string.pop();
+        for (name, value) in self.unknown_fields.iter() {
+            settings_string.push_str(&format!(",{name}:{value}"));
+        }
+        settings_string.push(')');
+        settings_string
+    }
+
+    /// Missing fields get their defaults, unknown ones are kept
+    fn parse(contents: &str) -> Result<Self, ron::error::SpannedError> {
+        let mut settings: Settings = ron::from_str(contents)?;
+        settings.keybinds.fill_missing();
+        let known_fields = top_level_fields(&ron::ser::to_string(&Settings::default()).unwrap());
+        settings.unknown_fields = top_level_fields(contents)
+            .into_iter()
+            .filter(|(name, _)| !known_fields.iter().any(|(known, _)| known == name))
+            .collect();
+        Ok(settings)
+    }
+}
+
+/// Splits the outermost `(name: value, ...)` of a RON file into its fields, keeping each value's
+/// text as it is
+fn top_level_fields(text: &str) -> Vec<(String, String)> {
+    // Skip extension lines like `#![enable(implicit_some)]`
+    let body_start = text
+        .lines()
+        .take_while(|line| line.trim_start().starts_with("#!"))
+        .map(|line| line.len() + 1)
+        .sum::<usize>()
+        .min(text.len());
+    let Some(open) = text[body_start..].find('(').map(|index| index + body_start) else {
+        return Vec::new();
+    };
+
+    let mut fields = Vec::new();
+    let mut depth = 0;
+    let mut in_string = false;
+    let mut escaped = false;
+    let mut field_start = open + 1;
+    for (index, character) in text.char_indices().skip_while(|(index, _)| *index <= open) {
+        if in_string {
+            match character {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match character {
+            '"' => in_string = true,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' if depth > 0 => depth -= 1,
+            // Commas inside a value belong to it
+            ',' if depth > 0 => {}
+            ',' | ')' => {
+                if let Some((name, value)) = text[field_start..index].split_once(':') {
+                    fields.push((name.trim().to_string(), value.trim().to_string()));
+                }
+                if character == ')' {
+                    break;
+                }
+                field_start = index + 1;
+            }
+            _ => {}
         }
     }
+    fields
 }
-*/
+
+/// Where the settings are saved, e.g. `~/.config/vaic/settings.ron` on Linux
+pub fn settings_path() -> PathBuf {
+    dirs::config_dir()
+        .map(|folder| folder.join("vaic"))
+        .unwrap_or_default()
+        .join("settings.ron")
+}
+
+/// Older versions kept the settings next to the program
+const OLD_SETTINGS_PATH: &str = ".settings.ron";
+
+/// Set when the settings file couldn't be read, so the UI can tell the user why their settings
+/// were reset
+#[derive(Resource, Default, Debug)]
+pub struct SettingsLoadError(pub Option<String>);
 
 impl Default for Settings {
     fn default() -> Self {
@@ -34,7 +195,15 @@ impl Default for Settings {
             translate_sensitivity: Vec2::splat(0.2),
             zoom_sensitivity: 1.0,
             fps_cap: 60.0,
-            precision_snap: false,
+            precision_snap: true,
+            snap_mode: SnapMode::Grid,
+            snap_distance: 0.20,
+            snap_angle: 15.0,
+            keybinds: Keybinds::default(),
+            theme: Theme::Dark,
+            unit: LengthUnit::Inches,
+            rule_set: String::new(),
+            unknown_fields: Vec::new(),
         }
     }
 }
@@ -44,21 +213,117 @@ pub struct SettingsPlugin;
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
         app.init_resource::<Settings>()
+            .init_resource::<SettingsLoadError>()
             .add_systems(PreStartup, load_or_create_settings_file);
     }
 }
 
-fn load_or_create_settings_file(mut settings: ResMut<Settings>) {
-    if let Ok(mut file) = File::open(".settings.ron") {
-        let mut contents = String::new();
-        file.read_to_string(&mut contents).unwrap();
-        let bruh: ron::error::SpannedResult<Settings> = ron::from_str(contents.as_str());
-        *settings = bruh.expect("Couldn't convert to Settings in load_or_create_settings_file");
-    } else {
-        let mut file = File::create(".settings.ron")
-            .expect("Couldn't create .settings.ron file in load_or_create_settings_file");
-        let settings_string = ron::ser::to_string(&(*settings)).unwrap();
-        file.write_all(settings_string.as_bytes())
-            .expect("Couldn't write to file in load_or_create_settings_file");
+fn load_or_create_settings_file(
+    mut settings: ResMut<Settings>,
+    mut load_error: ResMut<SettingsLoadError>,
+) {
+    let path = settings_path();
+    if !path.exists() {
+        if let Some(folder) = path.parent() {
+            let _ = fs::create_dir_all(folder);
+        }
+        let _ = fs::rename(OLD_SETTINGS_PATH, &path);
+    }
+
+    match fs::read_to_string(&path) {
+        Ok(contents) => match Settings::parse(&contents) {
+            Ok(loaded) => *settings = loaded,
+            Err(error) => {
+                let backup = path.with_extension("ron.bak");
+                let mut message = format!(
+                    "Your settings couldn't be read ({error}), so the defaults are being used."
+                );
+                if fs::rename(&path, &backup).is_ok() {
+                    message.push_str(&format!(" The old file was moved to {}", backup.display()));
+                }
+                load_error.0 = Some(message);
+                settings.write_changes();
+            }
+        },
+        Err(error) if error.kind() == ErrorKind::NotFound => settings.write_changes(),
+        Err(error) => {
+            load_error.0 = Some(format!(
+                "Couldn't open {} ({error}), so the defaults are being used.",
+                path.display()
+            ));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::keybinds::{InputAction, KeyChord};
+
+    #[test]
+    fn old_files_get_the_newer_fields() {
+        let settings = Settings::parse("(control_state: 1, fps_cap: 30.0)").unwrap();
+        assert_eq!(
+            settings,
+            Settings {
+                control_state: 1,
+                fps_cap: 30.0,
+                ..default()
+            }
+        );
+    }
+
+    #[test]
+    fn missing_keybinds_get_their_defaults() {
+        let settings = Settings::parse("(keybinds: ({Undo: [\"Ctrl+Y\"]}))").unwrap();
+        let defaults = Keybinds::default();
+        let ctrl_y = "Ctrl+Y".parse::<KeyChord>().unwrap();
+        assert_eq!(settings.keybinds.chords(InputAction::Undo), [ctrl_y]);
+        for action in InputAction::ALL {
+            if action != InputAction::Undo {
+                assert_eq!(
+                    settings.keybinds.chords(action),
+                    defaults.chords(action),
+                    "{action:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_fields_survive_saving() {
+        let contents =
+            "(fps_cap: 30.0, from_the_future: Some((a: [1, 2], b: \"x, y)\")), theme: Light)";
+        let settings = Settings::parse(contents).unwrap();
+        assert_eq!(settings.theme, Theme::Light);
+        assert_eq!(
+            settings.unknown_fields,
+            [(
+                String::from("from_the_future"),
+                String::from("Some((a: [1, 2], b: \"x, y)\"))")
+            )]
+        );
+
+        let saved_again = Settings::parse(&settings.to_ron()).unwrap();
+        assert_eq!(saved_again, settings);
+    }
+
+    #[test]
+    fn splits_top_level_fields() {
+        let text = "#![enable(implicit_some)]\n(\n  a: 1,\n  b: [\"(\", {c: (2, 3)}],\n)";
+        assert_eq!(
+            top_level_fields(text),
+            [
+                (String::from("a"), String::from("1")),
+                (String::from("b"), String::from("[\"(\", {c: (2, 3)}]")),
+            ]
+        );
+        assert!(top_level_fields("not ron").is_empty());
+    }
+
+    #[test]
+    fn broken_files_are_errors() {
+        assert!(Settings::parse("(fps_cap: \"fast\")").is_err());
+        assert!(Settings::parse("(fps_cap: 30.0").is_err());
     }
 }
@@ -0,0 +1,222 @@
+use crate::{
+    actions::{Action, ActionList},
+    assembly::Assembly,
+    catalog,
+    move_objects::{CurrentlyMoving, Locked},
+    placing::PartName,
+    properties::{ColorOverride, Notes, PartProperties},
+    settings::Settings,
+    units::LengthUnit,
+};
+use bevy::prelude::*;
+use bevy_egui::EguiContexts;
+
+#[derive(Resource, Default, Debug)]
+pub struct InspectorState {
+    /// What the entity looked like before the edit that's in progress, so the whole edit (e.g. a
+    /// drag or typing a name) becomes one undo step
+    edit_start: Option<(Entity, Transform, PartProperties)>,
+}
+
+type InspectedQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        &'static mut Transform,
+        Option<&'static PartName>,
+        Option<&'static Assembly>,
+        Option<&'static Parent>,
+        Has<Locked>,
+        Option<&'static Name>,
+        Option<&'static ColorOverride>,
+        Option<&'static Notes>,
+    ),
+>;
+
+/// Shows the selected part's catalog info and lets its transform and properties be typed in
+pub fn inspector(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut state: ResMut<InspectorState>,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    mut inspected_query: InspectedQuery,
+    mut action_list: ResMut<ActionList>,
+    mut settings: ResMut<Settings>,
+) {
+    let selected = selected_query.get_single().ok();
+
+    // Selecting something else finishes the edit on the previous selection
+    if let Some((entity, _, _)) = state.edit_start {
+        if Some(entity) != selected {
+            if let Ok((transform, _, _, _, _, name, color, notes)) = inspected_query.get(entity) {
+                let properties = PartProperties::new(name, color, notes);
+                finish_edit(&mut state, *transform, properties, &mut action_list);
+            }
+            state.edit_start = None;
+        }
+    }
+
+    let Some(entity) = selected else {
+        return;
+    };
+    let Ok((mut transform, part_name, assembly, parent, locked, name, color, notes)) =
+        inspected_query.get_mut(entity)
+    else {
+        return;
+    };
+    let properties = PartProperties::new(name, color, notes);
+    let mut new_transform = *transform;
+    let mut new_properties = properties.clone();
+
+    let ctx = contexts.ctx_mut();
+    egui::SidePanel::right("Inspector")
+        .resizable(false)
+        .show(ctx, |ui| {
+            ui.heading("Inspector");
+            ui.separator();
+
+            match (part_name, assembly) {
+                (Some(part_name), _) => match catalog::find_by_scene_path(&part_name.0) {
+                    Some(entry) => {
+                        ui.label(format!("Part: {}", entry.name));
+                        ui.label(format!("Model: {}", entry.model));
+                        if !entry.symmetric {
+                            ui.label("Not symmetric, mirroring only turns it around");
+                        }
+                    }
+                    None => {
+                        ui.label(format!("Part: {}", part_name.0));
+                    }
+                },
+                (None, Some(assembly)) => {
+                    ui.label(format!("Assembly: {}", assembly.name));
+                }
+                (None, None) => {}
+            }
+            if locked {
+                ui.label("Locked");
+            }
+            ui.separator();
+
+            ui.add_enabled_ui(!locked, |ui| {
+                ui.horizontal(|ui| {
+                    ui.label("Position");
+                    // Same setting as in the settings screen, so it's saved right away
+                    let mut unit = settings.unit;
+                    egui::ComboBox::from_id_source("Inspector Unit")
+                        .selected_text(unit.suffix().trim())
+                        .show_ui(ui, |ui| {
+                            for option in LengthUnit::ALL {
+                                ui.selectable_value(&mut unit, option, option.suffix().trim());
+                            }
+                        });
+                    if unit != settings.unit {
+                        settings.unit = unit;
+                        settings.write_changes();
+                    }
+                });
+                if parent.is_some() {
+                    ui.label("(relative to its assembly)");
+                }
+                let unit = settings.unit;
+                let shown_position = new_transform.translation / unit.size();
+                let mut position = shown_position;
+                ui.horizontal(|ui| {
+                    for (axis, value) in ["X", "Y", "Z"].iter().zip(position.as_mut().iter_mut()) {
+                        ui.label(*axis);
+                        ui.add(
+                            egui::DragValue::new(value)
+                                .speed(0.05)
+                                .suffix(unit.suffix()),
+                        );
+                    }
+                });
+                if position != shown_position {
+                    new_transform.translation = position * unit.size();
+                }
+
+                ui.label("Rotation");
+                let (x, y, z) = new_transform.rotation.to_euler(EulerRot::XYZ);
+                let shown_degrees = Vec3::new(x.to_degrees(), y.to_degrees(), z.to_degrees());
+                let mut degrees = shown_degrees;
+                ui.horizontal(|ui| {
+                    for (axis, value) in ["X", "Y", "Z"].iter().zip(degrees.as_mut().iter_mut()) {
+                        ui.label(*axis);
+                        ui.add(egui::DragValue::new(value).speed(1.0).suffix("°"));
+                    }
+                });
+                // Only touch the rotation when it was edited, converting back and forth drifts
+                if degrees != shown_degrees {
+                    new_transform.rotation = Quat::from_euler(
+                        EulerRot::XYZ,
+                        degrees.x.to_radians(),
+                        degrees.y.to_radians(),
+                        degrees.z.to_radians(),
+                    );
+                }
+            });
+            ui.separator();
+
+            // Assemblies are renamed in the assembly window and don't have a color of their own
+            if part_name.is_some() {
+                ui.horizontal(|ui| {
+                    ui.label("Name");
+                    let mut text = new_properties.name.clone().unwrap_or_default();
+                    ui.text_edit_singleline(&mut text);
+                    new_properties.name = Some(text).filter(|text| !text.is_empty());
+                });
+
+                ui.horizontal(|ui| {
+                    let mut overridden = new_properties.color.is_some();
+                    ui.checkbox(&mut overridden, "Color");
+                    let color = new_properties.color.unwrap_or(Color::GRAY);
+                    let mut rgb = [color.r(), color.g(), color.b()];
+                    ui.add_enabled_ui(overridden, |ui| {
+                        ui.color_edit_button_rgb(&mut rgb);
+                    });
+                    new_properties.color = overridden.then(|| Color::rgb(rgb[0], rgb[1], rgb[2]));
+                });
+            }
+
+            ui.label("Notes");
+            ui.text_edit_multiline(&mut new_properties.notes);
+        });
+
+    if new_transform != *transform || new_properties != properties {
+        if state.edit_start.is_none() {
+            state.edit_start = Some((entity, *transform, properties.clone()));
+        }
+        *transform = new_transform;
+        if new_properties != properties {
+            new_properties.apply(&mut commands.entity(entity));
+        }
+    }
+
+    // The edit is done once nothing in the UI is being dragged or typed into
+    let editing = ctx.is_using_pointer() || ctx.memory(|memory| memory.focus().is_some());
+    if !editing {
+        finish_edit(&mut state, new_transform, new_properties, &mut action_list);
+    }
+}
+
+/// Puts the edit that was in progress into the undo list
+fn finish_edit(
+    state: &mut InspectorState,
+    transform: Transform,
+    properties: PartProperties,
+    action_list: &mut ActionList,
+) {
+    let Some((entity, start_transform, start_properties)) = state.edit_start.take() else {
+        return;
+    };
+    if transform != start_transform {
+        action_list
+            .0
+            .push(Action::Moved(vec![(entity, start_transform, transform)]));
+    }
+    if properties != start_properties {
+        action_list
+            .0
+            .push(Action::PropertiesChanged(entity, start_properties));
+    }
+}
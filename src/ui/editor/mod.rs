@@ -2,7 +2,7 @@ use crate::constraints::ConstrainState;
 
 use bevy_asset_loader::prelude::*;
 
-use self::top_bar::update_top_bar_timer;
+use self::top_bar::{update_snap_indicator, update_top_bar_timer};
 
 use super::UIState;
 use bevy::{gltf::Gltf, prelude::*};
@@ -11,7 +11,10 @@ pub mod part_selector;
 use part_selector::spawn_part_selector;
 
 pub mod parts_list;
-use parts_list::spawn_parts_list;
+
+pub mod inspector;
+
+pub mod view_cube;
 
 pub mod top_bar;
 use top_bar::spawn_top_bar;
@@ -35,8 +38,9 @@ pub struct Models {
 
 impl Plugin for EditorPlugin {
     fn build(&self, app: &mut App) {
-        app.add_event::<parts_list::RefreshPartsList>()
-            .init_state::<LoadingStates>()
+        app.init_state::<LoadingStates>()
+            .init_resource::<parts_list::PartsListState>()
+            .init_resource::<inspector::InspectorState>()
             .add_loading_state(
                 LoadingState::new(LoadingStates::AssetLoading)
                     .continue_to_state(LoadingStates::Next)
@@ -45,19 +49,17 @@ impl Plugin for EditorPlugin {
             .add_systems(OnEnter(LoadingStates::Next), handle::load_models_early)
             .add_systems(
                 OnEnter(UIState::Editor),
-                (spawn_part_selector, spawn_parts_list, spawn_top_bar),
+                (spawn_part_selector, spawn_top_bar),
             )
             .add_systems(OnExit(UIState::Editor), despawn_ui)
             .add_systems(
                 Update,
                 (
-                    (
-                        part_selector::button_system,
-                        parts_list::update_parts_list,
-                        parts_list::refresh_parts_list,
-                    )
-                        .run_if(in_state(ConstrainState::NotConstraining)),
+                    part_selector::button_system.run_if(in_state(ConstrainState::NotConstraining)),
+                    (parts_list::parts_list, parts_list::highlight_hovered_entry).chain(),
+                    (inspector::inspector, view_cube::view_cube).chain(),
                     update_top_bar_timer,
+                    update_snap_indicator,
                 )
                     .run_if(in_state(UIState::Editor)),
             );
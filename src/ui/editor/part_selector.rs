@@ -110,13 +110,10 @@ pub fn spawn_part_selector(
 }
 
 pub fn get_parts() -> Vec<String> {
-    return vec![
-        String::from("Duck"),
-        String::from("Cube"),
-        String::from("Penguin"),
-        String::from("2x25 C-Channel"),
-        String::from("2x1 C-Channel"),
-    ];
+    return crate::catalog::CATALOG
+        .iter()
+        .map(|entry| String::from(entry.name))
+        .collect();
 }
 
 pub fn button_system(
@@ -171,24 +168,16 @@ pub fn button_system(
 }
 
 pub fn get_model_name(text: &str) -> &str {
-    match text {
-        "Duck" => "low_poly_duck.glb",
-        "Cube" => "cube.glb",
-        "Penguin" => "penguin.glb",
-        "2x25 C-Channel" => "c-channel-1x2x1x25.glb",
-        "2x1 C-Channel" => "c-channel-1x2x1x1.glb",
-        _ => "duck",
+    match crate::catalog::find_by_name(text) {
+        Some(entry) => entry.model,
+        None => "duck",
     }
 }
 
 pub fn reverse_model_name(text: String) -> String {
-    match text.as_str() {
-        "models/low_poly_duck.glb#Scene0" => String::from("Duck"),
-        "models/cube.glb#Scene0" => String::from("Cube"),
-        "models/penguin.glb#Scene0" => String::from("Penguin"),
-        "models/c-channel-1x2x1x25.glb#Scene0" => String::from("2x25 C-Channel"),
-        "models/c-channel-1x2x1x1.glb#Scene0" => String::from("2x1 C-Channel"),
-        _ => {
+    match crate::catalog::find_by_scene_path(text.as_str()) {
+        Some(entry) => String::from(entry.name),
+        None => {
             panic!("{}", text.as_str());
         }
     }
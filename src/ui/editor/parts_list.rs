@@ -1,266 +1,380 @@
-use crate::{actions::ActionList, placing::PlacedPart, ui::editor::EditorUIComponent};
+use crate::{
+    actions::{Action, ActionList},
+    assembly::Assembly,
+    bounds::{world_bounds, BoundsQuery},
+    move_objects::{CurrentlyMoving, Locked, MoveObjectsState},
+    placing::{CurrentlyPlacing, PartName},
+};
 use bevy::prelude::*;
-use bevy_blur_regions::BlurRegion;
+use bevy_egui::EguiContexts;
 
-#[derive(Component)]
-pub struct PartsList;
+/// Parts and assemblies that aren't inside of an assembly
+type TopLevelFilter = (
+    Or<(With<PartName>, With<Assembly>)>,
+    Without<Parent>,
+    Without<CurrentlyPlacing>,
+);
 
-pub fn spawn_parts_list(
+#[derive(Resource, Default, Debug)]
+pub struct PartsListState {
+    /// Entry being renamed and the name typed so far
+    renaming: Option<(Entity, String)>,
+    dragging: Option<Entity>,
+    hovered: Option<Entity>,
+}
+
+/// A snapshot of one line in the list, so the UI doesn't have to hold onto any queries
+struct Entry {
+    entity: Entity,
+    label: String,
+    hidden: bool,
+    locked: bool,
+    selected: bool,
+    /// `Some` for assemblies
+    children: Option<Vec<Entry>>,
+}
+
+/// Everything the list can do, applied after the UI is drawn
+enum ListCommand {
+    Select {
+        entity: Entity,
+        add: bool,
+    },
+    Rename(Entity, String),
+    /// Entity and whether it's hidden right now
+    ToggleHidden(Entity, bool),
+    /// Entity and whether it's locked right now
+    ToggleLocked(Entity, bool),
+    Delete(Entity),
+    /// Moves an entry into an assembly, or out to the top level
+    Regroup {
+        entity: Entity,
+        into: Option<Entity>,
+    },
+}
+
+type EntryQuery<'w, 's> = Query<
+    'w,
+    's,
+    (
+        Option<&'static PartName>,
+        Option<&'static Assembly>,
+        Option<&'static Name>,
+        &'static Visibility,
+        Has<Locked>,
+        Has<CurrentlyMoving>,
+        Option<&'static Children>,
+    ),
+>;
+
+fn build_entry(entity: Entity, entry_query: &EntryQuery) -> Option<Entry> {
+    let (part_name, assembly, name, visibility, locked, selected, children) =
+        entry_query.get(entity).ok()?;
+    let (label, children) = match (part_name, assembly) {
+        (Some(part_name), _) => (
+            crate::catalog::find_by_scene_path(&part_name.0)
+                .map_or(part_name.0.clone(), |entry| entry.name.to_string()),
+            None,
+        ),
+        (None, Some(assembly)) => (
+            assembly.name.clone(),
+            Some(
+                children
+                    .into_iter()
+                    .flat_map(|children| children.iter())
+                    .filter_map(|child| build_entry(*child, entry_query))
+                    .collect(),
+            ),
+        ),
+        (None, None) => return None,
+    };
+    Some(Entry {
+        entity,
+        label: name.map(|name| name.to_string()).unwrap_or(label),
+        hidden: *visibility == Visibility::Hidden,
+        locked,
+        selected,
+        children,
+    })
+}
+
+/// Shows every placed part, with assemblies as collapsible entries
+pub fn parts_list(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
-    placed_already: Res<ActionList>,
+    mut contexts: EguiContexts,
+    mut state: ResMut<PartsListState>,
+    top_level_query: Query<Entity, TopLevelFilter>,
+    entry_query: EntryQuery,
+    transform_query: Query<(&Transform, &GlobalTransform)>,
+    parent_query: Query<&Parent>,
+    selected_query: Query<Entity, With<CurrentlyMoving>>,
+    mut moving_state: ResMut<NextState<MoveObjectsState>>,
+    mut action_list: ResMut<ActionList>,
 ) {
-    commands
-        .spawn((
-            NodeBundle {
-                style: Style {
-                    width: Val::Percent(15.0),
-                    height: Val::Percent(80.0),
-                    left: Val::Percent(0.0), // 80% - 15% - 5%
-                    align_self: AlignSelf::Center,
-                    justify_content: JustifyContent::Start,
-                    align_items: AlignItems::Center,
-                    flex_direction: FlexDirection::Column,
-                    display: Display::Flex,
-                    overflow: Overflow::clip_y(),
-                    ..default()
-                },
-                background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.1)),
-                ..default()
-            },
-            EditorUIComponent,
-            PartsList,
-            BlurRegion,
-        ))
-        .with_children(|parent| {
-            // Parts header
-            parent
-                .spawn(NodeBundle {
-                    background_color: BackgroundColor(Color::rgba(0.0, 0.0, 0.0, 0.1)),
-                    style: Style {
-                        width: Val::Percent(100.0),
-                        align_items: AlignItems::Center,
-                        justify_content: JustifyContent::Center,
-                        display: Display::Flex,
-                        ..default()
-                    },
-                    ..default()
-                })
-                .with_children(|parent| {
-                    parent.spawn((
-                        TextBundle {
-                            text: Text::from_section(
-                                "Parts List",
-                                TextStyle {
-                                    font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
-                                    font_size: 24.0,
-                                    ..default()
-                                },
-                            ),
-                            ..default()
-                        },
-                        Label,
-                    ));
+    let mut top_level: Vec<Entity> = top_level_query.iter().collect();
+    // Oldest first, like the order things were placed in
+    top_level.sort();
+    let entries: Vec<Entry> = top_level
+        .into_iter()
+        .filter_map(|entity| build_entry(entity, &entry_query))
+        .collect();
+
+    let mut list_commands = vec![];
+    state.hovered = None;
+    egui::SidePanel::left("Parts List")
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading("Parts List");
+            ui.separator();
+            let mut drop_target = None;
+            egui::ScrollArea::vertical()
+                .auto_shrink([false; 2])
+                .show(ui, |ui| {
+                    for entry in entries.iter() {
+                        show_entry(ui, entry, &mut state, &mut list_commands, &mut drop_target);
+                    }
                 });
-            // End Parts header
-            // Show already placed parts
-            for part in placed_already.0.iter() {
-                if part.is_placeholder() {
+
+            if let Some(dragging) = state.dragging {
+                if ui.input(|input| input.pointer.any_released()) {
+                    // Dropping anywhere else in the list takes it out of its assembly
+                    if drop_target.is_some() || ui.ui_contains_pointer() {
+                        list_commands.push(ListCommand::Regroup {
+                            entity: dragging,
+                            into: drop_target,
+                        });
+                    }
+                    state.dragging = None;
+                }
+            }
+        });
+
+    for list_command in list_commands {
+        match list_command {
+            ListCommand::Select { entity, add } => {
+                if add && selected_query.contains(entity) {
+                    commands.entity(entity).remove::<CurrentlyMoving>();
                     continue;
                 }
-                if !part.is_placed() {
-                    return;
+                if !add {
+                    for selected in selected_query.iter() {
+                        commands.entity(selected).remove::<CurrentlyMoving>();
+                    }
                 }
-                parent
-                    .spawn(NodeBundle {
-                        background_color: BackgroundColor(Color::hex("777777").unwrap()),
-                        style: Style {
-                            width: Val::Percent(100.0),
-                            align_items: AlignItems::Center,
-                            justify_content: JustifyContent::Center,
-                            overflow: Overflow::clip_y(),
-                            display: Display::Flex,
-                            border: UiRect::bottom(Val::Px(1.0)),
-                            ..default()
-                        },
-                        border_color: BorderColor(Color::hex("555555").unwrap()),
-                        ..default()
-                    })
-                    .with_children(|parent| {
-                        let part: PlacedPart = part.clone().into();
-                        parent.spawn((
-                            TextBundle {
-                                text: Text::from_section(
-                                    crate::ui::editor::part_selector::reverse_model_name(
-                                        part.name.clone(),
-                                    ),
-                                    TextStyle {
-                                        font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
-                                        font_size: 24.0,
-                                        ..default()
-                                    },
-                                ),
-                                ..default()
-                            },
-                            Label,
-                        ));
-                    });
+                commands.entity(entity).insert(CurrentlyMoving);
+                moving_state.set(MoveObjectsState::Moving);
             }
-        });
+            ListCommand::Rename(entity, name) => {
+                if entry_query.get(entity).is_ok_and(|entry| entry.1.is_some()) {
+                    commands.entity(entity).insert(Assembly { name });
+                } else if name.trim().is_empty() {
+                    commands.entity(entity).remove::<Name>();
+                } else {
+                    commands.entity(entity).insert(Name::new(name));
+                }
+            }
+            ListCommand::ToggleHidden(entity, hidden) => {
+                commands.entity(entity).insert(if hidden {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                });
+            }
+            ListCommand::ToggleLocked(entity, locked) => {
+                if locked {
+                    commands.entity(entity).remove::<Locked>();
+                } else {
+                    commands
+                        .entity(entity)
+                        .insert(Locked)
+                        .remove::<CurrentlyMoving>();
+                }
+            }
+            ListCommand::Delete(entity) => {
+                if let Ok((transform, _)) = transform_query.get(entity) {
+                    let name = build_entry(entity, &entry_query)
+                        .map(|entry| entry.label)
+                        .unwrap_or_default();
+                    action_list
+                        .0
+                        .push(Action::Deleted(entity, name, transform.clone()));
+                    commands.entity(entity).despawn_recursive();
+                }
+            }
+            ListCommand::Regroup { entity, into } => {
+                regroup(&mut commands, entity, into, &transform_query, &parent_query);
+            }
+        }
+    }
 }
 
-pub fn update_parts_list(
-    mut commands: Commands,
-    mut parts_list_query: Query<Entity, With<PartsList>>,
-    recently_placed: Res<ActionList>,
-    asset_server: Res<AssetServer>,
+fn show_entry(
+    ui: &mut egui::Ui,
+    entry: &Entry,
+    state: &mut PartsListState,
+    list_commands: &mut Vec<ListCommand>,
+    drop_target: &mut Option<Entity>,
 ) {
-    if !recently_placed.is_changed() {
-        return;
+    let mut text = entry.label.clone();
+    if entry.hidden {
+        text += " (hidden)";
     }
-    if recently_placed.0.is_empty() {
-        return;
+    if entry.locked {
+        text += " (locked)";
     }
-    if !recently_placed.0[recently_placed.0.len() - 1].is_placed() {
-        return;
-    }
-    let parts_list = parts_list_query.get_single_mut().unwrap();
 
-    let new_part = NodeBundle {
-        background_color: BackgroundColor(Color::hex("777777").unwrap()),
-        style: Style {
-            width: Val::Percent(100.0),
-            align_items: AlignItems::Center,
-            justify_content: JustifyContent::Center,
-            display: Display::Flex,
-            border: UiRect::bottom(Val::Px(1.0)),
-            ..default()
-        },
-        border_color: BorderColor(Color::hex("555555").unwrap()),
-        ..default()
+    let response = match &entry.children {
+        None => entry_row(ui, entry, text, state, list_commands),
+        Some(children) => {
+            let id = ui.make_persistent_id(entry.entity);
+            let (_, header, _) = egui::collapsing_header::CollapsingState::load_with_default_open(
+                ui.ctx(),
+                id,
+                true,
+            )
+            .show_header(ui, |ui| entry_row(ui, entry, text, state, list_commands))
+            .body(|ui| {
+                for child in children.iter() {
+                    show_entry(ui, child, state, list_commands, drop_target);
+                }
+            });
+            let response = header.inner;
+            if state
+                .dragging
+                .is_some_and(|dragging| dragging != entry.entity)
+                && ui.rect_contains_pointer(response.rect)
+            {
+                *drop_target = Some(entry.entity);
+                ui.painter()
+                    .rect_stroke(response.rect, 2.0, ui.visuals().selection.stroke);
+            }
+            response
+        }
     };
 
-    let part: PlacedPart = recently_placed.0[recently_placed.0.len() - 1]
-        .clone()
-        .into();
-
-    let recently_placed_stripped =
-        crate::ui::editor::part_selector::reverse_model_name(part.name.clone());
-    let new_part_text = (
-        TextBundle {
-            text: Text::from_section(
-                recently_placed_stripped,
-                TextStyle {
-                    font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
-                    font_size: 24.0,
-                    ..default()
-                },
-            ),
-            ..default()
-        },
-        Label,
-    );
-
-    let new_part_entity = commands.spawn(new_part).id();
-    let new_part_text_entity = commands.spawn(new_part_text).id();
+    if response.hovered() {
+        state.hovered = Some(entry.entity);
+    }
+}
 
-    commands
-        .entity(new_part_entity)
-        .add_child(new_part_text_entity);
+/// The clickable line of an entry, or a text box while it's being renamed
+fn entry_row(
+    ui: &mut egui::Ui,
+    entry: &Entry,
+    text: String,
+    state: &mut PartsListState,
+    list_commands: &mut Vec<ListCommand>,
+) -> egui::Response {
+    if let Some((renaming, name)) = state.renaming.as_mut() {
+        if *renaming == entry.entity {
+            let response = ui.text_edit_singleline(name);
+            response.request_focus();
+            if response.lost_focus() {
+                list_commands.push(ListCommand::Rename(entry.entity, name.clone()));
+                state.renaming = None;
+            }
+            return response;
+        }
+    }
 
-    commands.entity(parts_list).add_child(new_part_entity);
+    let response = ui
+        .selectable_label(entry.selected, text)
+        .interact(egui::Sense::click_and_drag());
+    if response.double_clicked() {
+        state.renaming = Some((entry.entity, entry.label.clone()));
+    } else if response.clicked() {
+        list_commands.push(ListCommand::Select {
+            entity: entry.entity,
+            add: ui.input(|input| input.modifiers.ctrl),
+        });
+    }
+    if response.drag_started() {
+        state.dragging = Some(entry.entity);
+    }
+    response.context_menu(|ui| {
+        if ui.button("Rename").clicked() {
+            state.renaming = Some((entry.entity, entry.label.clone()));
+            ui.close_menu();
+        }
+        if ui
+            .button(if entry.hidden { "Show" } else { "Hide" })
+            .clicked()
+        {
+            list_commands.push(ListCommand::ToggleHidden(entry.entity, entry.hidden));
+            ui.close_menu();
+        }
+        if ui
+            .button(if entry.locked { "Unlock" } else { "Lock" })
+            .clicked()
+        {
+            list_commands.push(ListCommand::ToggleLocked(entry.entity, entry.locked));
+            ui.close_menu();
+        }
+        if ui.button("Delete").clicked() {
+            list_commands.push(ListCommand::Delete(entry.entity));
+            ui.close_menu();
+        }
+    });
+    response
 }
 
-#[derive(Event)]
-pub struct RefreshPartsList;
-
-pub fn refresh_parts_list(
-    mut commands: Commands,
-    mut parts_list_query: Query<Entity, With<PartsList>>,
-    recently_placed: Res<ActionList>,
-    asset_server: Res<AssetServer>,
-    mut refresh_parts_list_reader: EventReader<RefreshPartsList>,
+/// Keeps the entry where it is in the world while moving it into (or out of) an assembly
+fn regroup(
+    commands: &mut Commands,
+    entity: Entity,
+    into: Option<Entity>,
+    transform_query: &Query<(&Transform, &GlobalTransform)>,
+    parent_query: &Query<&Parent>,
 ) {
-    for _event in refresh_parts_list_reader.read() {
-        let parts_list = parts_list_query.get_single_mut().unwrap();
-        commands.entity(parts_list).despawn_descendants();
-        let header = commands
-            .spawn(NodeBundle {
-                background_color: BackgroundColor(Color::hex("666666").unwrap()),
-                style: Style {
-                    width: Val::Percent(100.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Center,
-                    display: Display::Flex,
-                    overflow: Overflow::clip_y(),
-                    ..default()
-                },
-                ..default()
-            })
-            .with_children(|parent| {
-                parent.spawn((
-                    TextBundle {
-                        text: Text::from_section(
-                            "Parts List",
-                            TextStyle {
-                                font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
-                                font_size: 24.0,
-                                ..default()
-                            },
-                        ),
-                        ..default()
-                    },
-                    Label,
-                ));
-            })
-            .id();
-
-        commands.entity(parts_list).add_child(header);
-        for action in recently_placed.0.iter() {
-            if !action.is_placed() {
-                continue;
+    let Ok((_, global_transform)) = transform_query.get(entity) else {
+        return;
+    };
+    match into {
+        Some(assembly) => {
+            // An assembly can't end up inside of itself
+            let mut current = assembly;
+            loop {
+                if current == entity {
+                    return;
+                }
+                match parent_query.get(current) {
+                    Ok(parent) => current = parent.get(),
+                    Err(_) => break,
+                }
             }
-            let new_part = NodeBundle {
-                background_color: BackgroundColor(Color::hex("777777").unwrap()),
-                style: Style {
-                    width: Val::Percent(100.0),
-                    align_items: AlignItems::Center,
-                    justify_content: JustifyContent::Center,
-                    display: Display::Flex,
-                    border: UiRect::bottom(Val::Px(1.0)),
-                    ..default()
-                },
-                border_color: BorderColor(Color::hex("555555").unwrap()),
-                ..default()
+            let Ok((_, assembly_global_transform)) = transform_query.get(assembly) else {
+                return;
             };
-
-            let placed: PlacedPart = action.clone().into();
-
-            let recently_placed_stripped =
-                crate::ui::editor::part_selector::reverse_model_name(placed.name.clone());
-            let new_part_text = (
-                TextBundle {
-                    text: Text::from_section(
-                        recently_placed_stripped,
-                        TextStyle {
-                            font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
-                            font_size: 24.0,
-                            ..default()
-                        },
-                    ),
-                    ..default()
-                },
-                Label,
-            );
-
-            let new_part_entity = commands.spawn(new_part).id();
-            let new_part_text_entity = commands.spawn(new_part_text).id();
-
             commands
-                .entity(new_part_entity)
-                .add_child(new_part_text_entity);
-
-            commands.entity(parts_list).add_child(new_part_entity);
+                .entity(entity)
+                .insert(global_transform.reparented_to(assembly_global_transform))
+                .set_parent(assembly);
+        }
+        None => {
+            if parent_query.contains(entity) {
+                commands
+                    .entity(entity)
+                    .insert(global_transform.compute_transform())
+                    .remove_parent();
+            }
         }
     }
 }
+
+/// Outlines whatever is hovered in the parts list
+pub fn highlight_hovered_entry(
+    state: Res<PartsListState>,
+    children_query: Query<&Children>,
+    aabb_query: BoundsQuery,
+    mut gizmos: Gizmos,
+) {
+    let Some(hovered) = state.hovered else {
+        return;
+    };
+    if let Some((min, max)) = world_bounds(hovered, &children_query, &aabb_query) {
+        gizmos.cuboid(
+            Transform::from_translation((min + max) / 2.0).with_scale(max - min),
+            Color::YELLOW,
+        );
+    }
+}
@@ -1,4 +1,7 @@
+use crate::keybinds::{ActionState, InputAction};
+use crate::move_objects::snap_indicator_text;
 use crate::saving::{save_timer, SaveIcon};
+use crate::settings::Settings;
 use crate::ui::editor::EditorUIComponent;
 use bevy::prelude::*;
 use bevy_blur_regions::BlurRegion;
@@ -9,10 +12,15 @@ pub struct EditorTopBar;
 #[derive(Component)]
 pub struct EditorTopBarSaveTimer;
 
+/// Shows what dragged parts snap to
+#[derive(Component)]
+pub struct EditorTopBarSnap;
+
 pub fn spawn_top_bar(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     last_save: Res<save_timer::MostRecentSave>,
+    settings: Res<Settings>,
 ) {
     commands
         .spawn((
@@ -52,6 +60,26 @@ pub fn spawn_top_bar(
                 EditorTopBarSaveTimer,
             ));
 
+            parent.spawn((
+                TextBundle {
+                    style: Style {
+                        align_self: AlignSelf::Center,
+                        margin: UiRect::horizontal(Val::Px(20.0)),
+                        ..default()
+                    },
+                    text: Text::from_section(
+                        snap_indicator_text(&settings, true),
+                        TextStyle {
+                            font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
+                            font_size: 16.0,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    ..default()
+                },
+                EditorTopBarSnap,
+            ));
+
             parent.spawn(ImageBundle {
                 image: UiImage {
                     texture: asset_server.load("images/save_icon.png"),
@@ -104,3 +132,16 @@ pub fn update_top_bar_timer(
         );
     }
 }
+
+pub fn update_snap_indicator(
+    mut snap_text: Query<&mut Text, With<EditorTopBarSnap>>,
+    settings: Res<Settings>,
+    actions: Res<ActionState>,
+) {
+    let text = snap_indicator_text(&settings, !actions.pressed(InputAction::DisableSnapping));
+    for mut snap_text in snap_text.iter_mut() {
+        if snap_text.sections[0].value != text {
+            snap_text.sections[0].value = text.clone();
+        }
+    }
+}
@@ -0,0 +1,193 @@
+use bevy::prelude::*;
+use bevy_editor_cam::{extensions::look_to::LookToTrigger, prelude::EditorCam};
+use bevy_egui::EguiContexts;
+
+use crate::movement::{FrameCamera, StandardView};
+
+/// Half the width of the cube on screen, in points
+const CUBE_SIZE: f32 = 30.0;
+
+/// The view for each face of the cube, along with the face's outward normal
+fn faces() -> impl Iterator<Item = (StandardView, Vec3)> {
+    StandardView::ALL
+        .into_iter()
+        .filter(|view| *view != StandardView::Isometric)
+        .map(|view| (view, -*view.facing()))
+}
+
+/// Where the edge and corner targets on each face start, on a cube from -1 to 1
+const INNER: f32 = 0.65;
+
+/// Directions along the face with this normal
+fn face_axes(normal: Vec3) -> (Vec3, Vec3) {
+    let u = if normal.y.abs() > 0.5 {
+        Vec3::X
+    } else {
+        Vec3::Y
+    };
+    (u, normal.cross(u))
+}
+
+/// Splits the face with this normal into a 3x3 grid of click targets. The middle looks straight
+/// at the face, the strips along its sides look at an edge and the squares in its corners at a
+/// corner. Each target is the direction from the middle of the cube to what it looks at, along
+/// with its corners
+fn face_targets(normal: Vec3) -> impl Iterator<Item = (IVec3, [Vec3; 4])> {
+    let (u, v) = face_axes(normal);
+    let range = |step: i32| match step {
+        -1 => (-1.0, -INNER),
+        0 => (-INNER, INNER),
+        _ => (INNER, 1.0),
+    };
+    (-1..=1).flat_map(move |i| {
+        (-1..=1).map(move |j| {
+            let (u_min, u_max) = range(i);
+            let (v_min, v_max) = range(j);
+            let target = (normal + u * i as f32 + v * j as f32).round().as_ivec3();
+            let corners = [
+                normal + u * u_max + v * v_max,
+                normal + u * u_min + v * v_max,
+                normal + u * u_min + v * v_min,
+                normal + u * u_max + v * v_min,
+            ];
+            (target, corners)
+        })
+    })
+}
+
+fn contains(polygon: &[egui::Pos2], point: egui::Pos2) -> bool {
+    let mut sign = 0.0;
+    for (index, a) in polygon.iter().enumerate() {
+        let b = polygon[(index + 1) % polygon.len()];
+        let cross = (b - *a).x * (point - *a).y - (b - *a).y * (point - *a).x;
+        if cross * sign < 0.0 {
+            return false;
+        }
+        if cross != 0.0 {
+            sign = cross;
+        }
+    }
+    true
+}
+
+/// A cube in the top right that turns along with the camera. Clicking a face, edge or corner
+/// looks at the cube from that side
+pub fn view_cube(
+    mut contexts: EguiContexts,
+    camera_query: Query<(Entity, &Transform, &EditorCam)>,
+    mut look_to: EventWriter<LookToTrigger>,
+    mut frame_events: EventWriter<FrameCamera>,
+) {
+    let Ok((camera, camera_transform, editor_camera)) = camera_query.get_single() else {
+        return;
+    };
+    let ctx = contexts.ctx_mut();
+    // Stay clear of the top bar and of side panels like the inspector
+    let screen = ctx.screen_rect();
+    let available = ctx.available_rect();
+    let offset = egui::vec2(
+        available.right() - screen.right() - 10.0,
+        screen.height() * 0.05 + 10.0,
+    );
+
+    let to_view = camera_transform.rotation.inverse();
+    let mut clicked = None;
+    egui::Area::new(egui::Id::new("View Cube"))
+        .anchor(egui::Align2::RIGHT_TOP, offset)
+        .show(ctx, |ui| {
+            let (rect, response) =
+                ui.allocate_exact_size(egui::Vec2::splat(CUBE_SIZE * 3.6), egui::Sense::click());
+            let center = rect.center();
+            let project = |point: Vec3| {
+                let point = to_view * point;
+                center + egui::vec2(point.x, -point.y) * CUBE_SIZE
+            };
+
+            type VisibleFace = (StandardView, Vec3, f32, Vec<(IVec3, [egui::Pos2; 4])>);
+            let mut visible: Vec<VisibleFace> = faces()
+                .filter_map(|(view, normal)| {
+                    let facing_camera = (to_view * normal).z;
+                    (facing_camera > 0.01).then(|| {
+                        let targets = face_targets(normal)
+                            .map(|(target, corners)| (target, corners.map(project)))
+                            .collect();
+                        (view, normal, facing_camera, targets)
+                    })
+                })
+                .collect();
+            visible.sort_by(|(_, _, a, _), (_, _, b, _)| a.total_cmp(b));
+
+            // Edges and corners are on more than one face, so they light up on all of them
+            let hovered = response.hover_pos().and_then(|pointer| {
+                visible.iter().rev().find_map(|(_, _, _, targets)| {
+                    targets
+                        .iter()
+                        .find(|(_, corners)| contains(corners, pointer))
+                        .map(|(target, _)| *target)
+                })
+            });
+            let painter = ui.painter();
+            let visuals = ui.visuals();
+            for (view, normal, facing_camera, targets) in visible.iter() {
+                for (target, corners) in targets {
+                    let fill = if hovered == Some(*target) {
+                        visuals.widgets.hovered.bg_fill
+                    } else {
+                        visuals.widgets.inactive.bg_fill
+                    };
+                    painter.add(egui::Shape::convex_polygon(
+                        corners.to_vec(),
+                        fill,
+                        visuals.widgets.noninteractive.bg_stroke,
+                    ));
+                }
+                let (u, v) = face_axes(*normal);
+                let outline = [u + v, v - u, -u - v, u - v]
+                    .map(|corner| project(*normal + corner))
+                    .to_vec();
+                painter.add(egui::Shape::closed_line(
+                    outline,
+                    visuals.widgets.inactive.fg_stroke,
+                ));
+                // Labels on faces seen from the side would just overlap
+                if *facing_camera > 0.3 {
+                    painter.text(
+                        project(*normal),
+                        egui::Align2::CENTER_CENTER,
+                        view.label(),
+                        egui::FontId::proportional(11.0),
+                        visuals.text_color(),
+                    );
+                }
+            }
+            if response.clicked() {
+                clicked = hovered;
+            }
+            ui.horizontal(|ui| {
+                if ui
+                    .small_button("Fit")
+                    .on_hover_text("Fit everything in view")
+                    .clicked()
+                {
+                    frame_events.send(FrameCamera::All);
+                }
+                if ui
+                    .small_button("Selection")
+                    .on_hover_text("Fit the selection in view")
+                    .clicked()
+                {
+                    frame_events.send(FrameCamera::Selection);
+                }
+            });
+        });
+
+    if let Some(target) = clicked {
+        let facing = Direction3d::new(-target.as_vec3()).unwrap();
+        look_to.send(LookToTrigger::auto_snap_up_direction(
+            facing,
+            camera,
+            camera_transform,
+            editor_camera,
+        ));
+    }
+}
@@ -1,7 +1,13 @@
 use bevy::prelude::*;
 use bevy_round_ui::prelude::*;
 
-use crate::{constraints::ConstrainState, move_objects::MoveObjectsState, placing::PlacingState};
+use crate::{
+    constraints::ConstrainState,
+    keybinds::{ActionState, InputAction},
+    measure::MeasureState,
+    move_objects::MoveObjectsState,
+    placing::PlacingState,
+};
 
 mod main_menu;
 use main_menu::MainMenuPlugin;
@@ -37,7 +43,8 @@ impl Plugin for UIPlugin {
                 handle_esc.run_if(
                     not(in_state(PlacingState::Placing))
                         .and_then(not(in_state(ConstrainState::Constraining)))
-                        .and_then(not(in_state(MoveObjectsState::Moving))),
+                        .and_then(not(in_state(MoveObjectsState::Moving)))
+                        .and_then(not(in_state(MeasureState::Measuring))),
                 ),
             )
             .add_plugins((
@@ -61,11 +68,11 @@ fn setup(mut commands: Commands) {
 }
 
 fn handle_esc(
-    keyboard: Res<ButtonInput<KeyCode>>,
+    actions: Res<ActionState>,
     mut ui_state: ResMut<NextState<UIState>>,
     current_state: Res<State<UIState>>,
 ) {
-    if keyboard.just_pressed(KeyCode::Escape) {
+    if actions.just_pressed(InputAction::Cancel) {
         match current_state.get() {
             UIState::Editor => {
                 ui_state.set(UIState::Pause);
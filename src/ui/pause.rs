@@ -1,4 +1,5 @@
 use super::UIState;
+use crate::bom::ExportBom;
 use bevy::app::AppExit;
 use bevy::prelude::*;
 use bevy_blur_regions::BlurRegion;
@@ -78,6 +79,32 @@ fn spawn_ui(
                         Label,
                     ));
                 });
+            parent
+                .spawn(ButtonBundle {
+                    style: Style {
+                        width: Val::Percent(BUTTON_WIDTH),
+                        height: Val::Percent(BUTTON_HEIGHT),
+                        top: Val::Percent(7.5),
+                        justify_content: JustifyContent::Center,
+                        align_items: AlignItems::Center,
+                        ..default()
+                    },
+                    background_color: BackgroundColor(Color::hex("777777").unwrap()),
+                    ..default()
+                })
+                .with_children(|parent| {
+                    parent.spawn((
+                        TextBundle::from_section(
+                            "Export BOM",
+                            TextStyle {
+                                font: asset_server.load("FiraMonoNerdFontMono-Bold.otf"),
+                                font_size: 32.0,
+                                ..default()
+                            },
+                        ),
+                        Label,
+                    ));
+                });
             parent
                 .spawn(ButtonBundle {
                     style: Style {
@@ -123,6 +150,7 @@ fn button_system(
     mut exit: EventWriter<AppExit>,
     mut ui_state: ResMut<NextState<UIState>>,
     mut window_query: Query<&mut Window>,
+    mut export_events: EventWriter<ExportBom>,
 ) {
     for (interaction, mut color, children) in &mut interaction_query {
         let mut window = window_query.get_single_mut().unwrap();
@@ -137,6 +165,9 @@ fn button_system(
                     "Settings" => {
                         ui_state.set(UIState::Settings);
                     }
+                    "Export BOM" => {
+                        export_events.send(ExportBom);
+                    }
                     _ => {
                         panic!("Bruh");
                     }
@@ -1,6 +1,14 @@
 use super::UIState;
+use crate::{
+    keybinds::{self, InputAction, Keybinds, Rebinding},
+    movement::ControlProfile,
+    settings::{Settings, SettingsLoadError, SnapMode, Theme},
+    units::LengthUnit,
+};
 use bevy::prelude::*;
+use bevy::window::WindowTheme;
 use bevy_blur_regions::BlurRegion;
+use bevy_egui::EguiContexts;
 use bevy_round_ui::prelude::*;
 
 pub struct SettingsPlugin;
@@ -8,8 +16,18 @@ pub struct SettingsPlugin;
 impl Plugin for SettingsPlugin {
     fn build(&self, app: &mut App) {
         app.add_systems(OnEnter(UIState::Settings), spawn_ui)
-            .add_systems(OnExit(UIState::Settings), despawn_ui)
-            .add_systems(Update, button_system.run_if(in_state(UIState::Settings)));
+            .add_systems(OnExit(UIState::Settings), (despawn_ui, save_settings))
+            .add_systems(
+                Update,
+                (button_system, settings_window).run_if(in_state(UIState::Settings)),
+            )
+            .add_systems(
+                Update,
+                (
+                    apply_theme.run_if(resource_changed::<Settings>),
+                    settings_load_warning,
+                ),
+            );
     }
 }
 
@@ -152,3 +170,235 @@ fn button_system(
         }
     }
 }
+
+fn settings_window(
+    mut contexts: EguiContexts,
+    mut current_settings: ResMut<Settings>,
+    mut rebinding: ResMut<Rebinding>,
+    keyboard: Res<ButtonInput<KeyCode>>,
+) {
+    // Edited on a copy so that the settings only count as changed when something really changed
+    let mut settings = current_settings.clone();
+    if let Some(action) = rebinding.0 {
+        if let Some(chord) = keybinds::captured_chord(&keyboard) {
+            let chords = settings.keybinds.0.entry(action).or_default();
+            if !chords.contains(&chord) {
+                chords.push(chord);
+            }
+            rebinding.0 = None;
+        }
+    }
+
+    egui::Window::new("Settings")
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_CENTER, [0.0, 0.0])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.heading("Camera");
+            ui.horizontal(|ui| {
+                ui.label("Controls");
+                for profile in ControlProfile::ALL {
+                    ui.radio_value(
+                        &mut settings.control_state,
+                        profile.index(),
+                        profile.label(),
+                    );
+                }
+            });
+            ui.label(ControlProfile::from_index(settings.control_state).description());
+            egui::Grid::new("Sensitivity")
+                .num_columns(3)
+                .show(ui, |ui| {
+                    ui.label("Rotate sensitivity");
+                    sensitivity_slider(ui, &mut settings.rotate_sensitivity.x, "X");
+                    sensitivity_slider(ui, &mut settings.rotate_sensitivity.y, "Y");
+                    ui.end_row();
+                    ui.label("Pan sensitivity");
+                    sensitivity_slider(ui, &mut settings.translate_sensitivity.x, "X");
+                    sensitivity_slider(ui, &mut settings.translate_sensitivity.y, "Y");
+                    ui.end_row();
+                    ui.label("Zoom sensitivity");
+                    sensitivity_slider(ui, &mut settings.zoom_sensitivity, "");
+                    ui.end_row();
+                });
+
+            ui.separator();
+            ui.heading("Display");
+            ui.horizontal(|ui| {
+                let mut capped = settings.fps_cap > 0.0;
+                ui.checkbox(&mut capped, "Limit FPS");
+                if !capped {
+                    settings.fps_cap = 0.0;
+                } else if settings.fps_cap <= 0.0 {
+                    settings.fps_cap = 60.0;
+                }
+                ui.add_enabled(
+                    capped,
+                    egui::Slider::new(&mut settings.fps_cap, 15.0..=240.0).suffix(" fps"),
+                );
+            });
+            ui.horizontal(|ui| {
+                ui.label("Units");
+                for unit in LengthUnit::ALL {
+                    ui.radio_value(&mut settings.unit, unit, unit.label());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Theme");
+                for theme in Theme::ALL {
+                    ui.radio_value(&mut settings.theme, theme, theme.label());
+                }
+            });
+
+            ui.separator();
+            ui.heading("Snapping");
+            ui.checkbox(&mut settings.precision_snap, "Snap while dragging")
+                .on_hover_text("Holding the key to stop snapping does the opposite");
+            ui.horizontal(|ui| {
+                ui.label("Snap to");
+                for mode in SnapMode::ALL {
+                    ui.radio_value(&mut settings.snap_mode, mode, mode.label());
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label(match settings.snap_mode {
+                    SnapMode::Geometry => "Snap within",
+                    _ => "Snap distance",
+                });
+                let unit = settings.unit;
+                let mut shown = unit.from_inches(settings.snap_distance);
+                if ui
+                    .add(
+                        egui::DragValue::new(&mut shown)
+                            .speed(0.01)
+                            .clamp_range(unit.from_inches(0.01)..=unit.from_inches(10.0))
+                            .suffix(unit.suffix()),
+                    )
+                    .changed()
+                {
+                    settings.snap_distance = unit.to_inches(shown);
+                }
+            });
+            ui.horizontal(|ui| {
+                ui.label("Snap angle");
+                ui.add(
+                    egui::DragValue::new(&mut settings.snap_angle)
+                        .speed(1.0)
+                        .clamp_range(1.0..=180.0)
+                        .suffix("°"),
+                );
+            });
+
+            ui.separator();
+            ui.heading("Keybindings");
+            keybinds_section(ui, &mut settings.keybinds, &mut rebinding);
+        });
+
+    if settings != *current_settings {
+        *current_settings = settings;
+    }
+}
+
+/// Tells the user their settings file was unreadable and has been reset
+fn settings_load_warning(mut contexts: EguiContexts, mut load_error: ResMut<SettingsLoadError>) {
+    let Some(message) = load_error.0.clone() else {
+        return;
+    };
+    egui::Window::new("Settings were reset")
+        .resizable(false)
+        .collapsible(false)
+        .anchor(egui::Align2::CENTER_TOP, [0.0, 40.0])
+        .show(contexts.ctx_mut(), |ui| {
+            ui.label(message);
+            if ui.button("OK").clicked() {
+                load_error.0 = None;
+            }
+        });
+}
+
+fn sensitivity_slider(ui: &mut egui::Ui, value: &mut f32, text: &str) {
+    ui.add(
+        egui::Slider::new(value, 0.05..=5.0)
+            .logarithmic(true)
+            .text(text),
+    );
+}
+
+/// Follows the theme setting in the egui windows, the background and the window decorations
+fn apply_theme(
+    mut contexts: EguiContexts,
+    settings: Res<Settings>,
+    mut clear_color: ResMut<ClearColor>,
+    mut window_query: Query<&mut Window>,
+) {
+    let (visuals, window_theme) = match settings.theme {
+        Theme::Dark => (egui::Visuals::dark(), WindowTheme::Dark),
+        Theme::Light => (egui::Visuals::light(), WindowTheme::Light),
+    };
+    contexts.ctx_mut().set_visuals(visuals);
+    clear_color.0 = settings.theme.clear_color();
+    for mut window in window_query.iter_mut() {
+        window.window_theme = Some(window_theme);
+    }
+}
+
+/// Lists every action with its keys. Clicking a key removes it, "+" binds the next key pressed
+fn keybinds_section(ui: &mut egui::Ui, keybinds: &mut Keybinds, rebinding: &mut Rebinding) {
+    egui::ScrollArea::vertical()
+        .max_height(300.0)
+        .show(ui, |ui| {
+            egui::Grid::new("Keybindings")
+                .striped(true)
+                .num_columns(2)
+                .show(ui, |ui| {
+                    for action in InputAction::ALL {
+                        let conflicts = keybinds.conflicts(action);
+                        if conflicts.is_empty() {
+                            ui.label(action.label());
+                        } else {
+                            let others: Vec<&str> =
+                                conflicts.iter().map(|other| other.label()).collect();
+                            ui.colored_label(egui::Color32::RED, action.label())
+                                .on_hover_text(format!("Same key as: {}", others.join(", ")));
+                        }
+
+                        ui.horizontal(|ui| {
+                            let mut removed = None;
+                            for (index, chord) in keybinds.chords(action).iter().enumerate() {
+                                if ui
+                                    .button(chord.to_string())
+                                    .on_hover_text("Click to remove")
+                                    .clicked()
+                                {
+                                    removed = Some(index);
+                                }
+                            }
+                            if let Some(index) = removed {
+                                if let Some(chords) = keybinds.0.get_mut(&action) {
+                                    chords.remove(index);
+                                }
+                            }
+
+                            if rebinding.0 == Some(action) {
+                                ui.label("Press a key...");
+                                if ui.small_button("Cancel").clicked() {
+                                    rebinding.0 = None;
+                                }
+                            } else if ui.small_button("+").clicked() {
+                                rebinding.0 = Some(action);
+                            }
+                        });
+                        ui.end_row();
+                    }
+                });
+        });
+    if ui.button("Reset to defaults").clicked() {
+        *keybinds = Keybinds::default();
+        rebinding.0 = None;
+    }
+}
+
+fn save_settings(settings: Res<Settings>, mut rebinding: ResMut<Rebinding>) {
+    rebinding.0 = None;
+    settings.write_changes();
+}
@@ -0,0 +1,94 @@
+use serde::{Deserialize, Serialize};
+
+// Lengths are stored in inches everywhere (VEX parts are designed in inches), the unit setting only
+// changes how they're shown and typed in
+
+/// Distance between two adjacent VEX holes
+pub const HOLE_PITCH: f32 = 0.5;
+
+pub const MILLIMETER: f32 = 1.0 / 25.4;
+
+/// How lengths are shown in the UI
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LengthUnit {
+    #[default]
+    Inches,
+    Millimeters,
+    /// Counted in hole pitches
+    Holes,
+}
+
+impl LengthUnit {
+    pub const ALL: [LengthUnit; 3] = [Self::Inches, Self::Millimeters, Self::Holes];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Self::Inches => "Inches",
+            Self::Millimeters => "Millimeters",
+            Self::Holes => "Holes",
+        }
+    }
+
+    /// Length of one of this unit, in inches
+    pub fn size(&self) -> f32 {
+        match self {
+            Self::Inches => 1.0,
+            Self::Millimeters => MILLIMETER,
+            Self::Holes => HOLE_PITCH,
+        }
+    }
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Self::Inches => " in",
+            Self::Millimeters => " mm",
+            Self::Holes => " holes",
+        }
+    }
+
+    pub fn from_inches(&self, inches: f32) -> f32 {
+        inches / self.size()
+    }
+
+    pub fn to_inches(&self, value: f32) -> f32 {
+        value * self.size()
+    }
+
+    /// e.g. "2.500 in", "63.50 mm" or "5.00 holes"
+    pub fn format(&self, inches: f32) -> String {
+        let decimals = match self {
+            Self::Inches => 3,
+            Self::Millimeters | Self::Holes => 2,
+        };
+        format!("{:.*}{}", decimals, self.from_inches(inches), self.suffix())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn converts_between_units() {
+        assert_eq!(LengthUnit::Inches.from_inches(2.5), 2.5);
+        assert!((LengthUnit::Millimeters.from_inches(1.0) - 25.4).abs() < 1e-4);
+        assert_eq!(LengthUnit::Holes.from_inches(2.5), 5.0);
+        assert!((LengthUnit::Millimeters.to_inches(25.4) - 1.0).abs() < 1e-6);
+        assert_eq!(LengthUnit::Holes.to_inches(3.0), 1.5);
+    }
+
+    #[test]
+    fn round_trips_through_every_unit() {
+        for unit in LengthUnit::ALL {
+            let inches = 3.75;
+            assert!((unit.to_inches(unit.from_inches(inches)) - inches).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn formats_with_the_unit() {
+        assert_eq!(LengthUnit::Inches.format(2.5), "2.500 in");
+        assert_eq!(LengthUnit::Millimeters.format(2.5), "63.50 mm");
+        assert_eq!(LengthUnit::Holes.format(2.5), "5.00 holes");
+    }
+}
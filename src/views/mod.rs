@@ -0,0 +1,109 @@
+use bevy::prelude::*;
+use bevy_editor_cam::prelude::EditorCam;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    movement::{self, CameraFlight},
+    saving::SAVE_FILE,
+};
+
+mod ui;
+use ui::ViewsUiPlugin;
+
+pub struct ViewsPlugin;
+
+impl Plugin for ViewsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<SavedViews>()
+            .add_plugins(ViewsUiPlugin)
+            .add_systems(Startup, load_saved_views);
+    }
+}
+
+/// A camera position to come back to during a design review
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct SavedView {
+    pub name: String,
+    pub translation: Vec3,
+    pub rotation: Quat,
+    /// `None` in perspective
+    pub orthographic_scale: Option<f32>,
+    /// How far in front of the camera it orbits around
+    pub anchor_depth: f64,
+}
+
+impl SavedView {
+    pub fn new(
+        name: String,
+        transform: &Transform,
+        projection: &Projection,
+        editor_camera: &EditorCam,
+    ) -> Self {
+        Self {
+            name,
+            translation: transform.translation,
+            rotation: transform.rotation,
+            orthographic_scale: match projection {
+                Projection::Orthographic(orthographic) => Some(orthographic.scale),
+                Projection::Perspective(_) => None,
+            },
+            anchor_depth: editor_camera.last_anchor_depth,
+        }
+    }
+
+    /// Flies the camera back to this view, switching projection first if needed
+    pub fn recall(
+        &self,
+        commands: &mut Commands,
+        camera: Entity,
+        transform: &Transform,
+        projection: &mut Projection,
+        editor_camera: &mut EditorCam,
+    ) {
+        let from_scale = match (&*projection, self.orthographic_scale) {
+            (Projection::Orthographic(orthographic), Some(_)) => orthographic.scale,
+            (_, Some(scale)) => {
+                *projection = movement::orthographic(scale);
+                scale
+            }
+            (Projection::Orthographic(_), None) => {
+                *projection = Projection::default();
+                1.0
+            }
+            (Projection::Perspective(_), None) => 1.0,
+        };
+        editor_camera.end_move();
+        editor_camera.last_anchor_depth = self.anchor_depth;
+        let to = Transform {
+            translation: self.translation,
+            rotation: self.rotation,
+            ..*transform
+        };
+        commands.entity(camera).insert(CameraFlight::new(
+            *transform,
+            to,
+            from_scale,
+            self.orthographic_scale.unwrap_or(1.0),
+        ));
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+pub struct SavedViews(pub Vec<SavedView>);
+
+/// Only the views are read back from the project file, the parts in it are left alone
+#[derive(Deserialize, Default)]
+#[serde(default)]
+struct ProjectViews {
+    views: Vec<SavedView>,
+}
+
+fn load_saved_views(mut saved_views: ResMut<SavedViews>) {
+    let Ok(contents) = std::fs::read_to_string(SAVE_FILE) else {
+        return;
+    };
+    // Projects saved before views existed are just a list of parts
+    if let Ok(project) = ron::from_str::<ProjectViews>(&contents) {
+        saved_views.0 = project.views;
+    }
+}
@@ -0,0 +1,115 @@
+use bevy::prelude::*;
+use bevy_editor_cam::prelude::EditorCam;
+use bevy_egui::EguiContexts;
+
+use crate::keybinds::{ActionState, InputAction};
+
+use super::{SavedView, SavedViews};
+
+pub struct ViewsUiPlugin;
+
+impl Plugin for ViewsUiPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<ViewsWindow>().add_systems(
+            Update,
+            (toggle_views_window, views_window).run_if(in_state(crate::ui::UIState::Editor)),
+        );
+    }
+}
+
+#[derive(Resource, Default, Debug)]
+struct ViewsWindow {
+    open: bool,
+    /// Name for the next saved view
+    name: String,
+}
+
+fn toggle_views_window(actions: Res<ActionState>, mut window: ResMut<ViewsWindow>) {
+    if actions.just_pressed(InputAction::ToggleViews) {
+        window.open = !window.open;
+    }
+}
+
+fn views_window(
+    mut commands: Commands,
+    mut contexts: EguiContexts,
+    mut window: ResMut<ViewsWindow>,
+    mut saved_views: ResMut<SavedViews>,
+    mut camera_query: Query<(Entity, &Transform, &mut Projection, &mut EditorCam)>,
+) {
+    if !window.open {
+        return;
+    }
+    let Ok((camera, transform, mut projection, mut editor_camera)) = camera_query.get_single_mut()
+    else {
+        return;
+    };
+
+    let mut open = true;
+    let mut recalled = None;
+    let mut removed = None;
+    egui::Window::new("Views")
+        .open(&mut open)
+        .resizable(false)
+        .show(contexts.ctx_mut(), |ui| {
+            ui.horizontal(|ui| {
+                ui.text_edit_singleline(&mut window.name);
+                let name = window.name.trim().to_string();
+                if ui
+                    .add_enabled(!name.is_empty(), egui::Button::new("Save current view"))
+                    .clicked()
+                {
+                    let view = SavedView::new(name, transform, &projection, &editor_camera);
+                    // Saving under an existing name updates that view
+                    match saved_views
+                        .0
+                        .iter_mut()
+                        .find(|saved| saved.name == view.name)
+                    {
+                        Some(saved) => *saved = view,
+                        None => saved_views.0.push(view),
+                    }
+                    window.name.clear();
+                }
+            });
+            ui.separator();
+
+            if saved_views.0.is_empty() {
+                ui.label("No saved views yet");
+            }
+            for (index, view) in saved_views.0.iter().enumerate() {
+                ui.horizontal(|ui| {
+                    let projection_name = match view.orthographic_scale {
+                        Some(_) => "orthographic",
+                        None => "perspective",
+                    };
+                    if ui
+                        .button(&view.name)
+                        .on_hover_text(format!("Go to this view ({projection_name})"))
+                        .clicked()
+                    {
+                        recalled = Some(index);
+                    }
+                    if ui.small_button("×").on_hover_text("Delete").clicked() {
+                        removed = Some(index);
+                    }
+                });
+            }
+        });
+    if !open {
+        window.open = false;
+    }
+
+    if let Some(view) = recalled.and_then(|index| saved_views.0.get(index)) {
+        view.recall(
+            &mut commands,
+            camera,
+            transform,
+            &mut projection,
+            &mut editor_camera,
+        );
+    }
+    if let Some(index) = removed {
+        saved_views.0.remove(index);
+    }
+}